@@ -1,27 +1,39 @@
 use xiapi::number_devices;
 use xiapi::open_device;
+use xiapi::CameraGroup;
+use xiapi::SyncConfig;
+use xiapi::XI_ACQ_TIMING_MODE::XI_ACQ_TIMING_MODE_FRAME_RATE;
 use xiapi::XI_RETURN;
-use xiapi::XI_TRG_SOURCE::XI_TRG_SOFTWARE;
 
+// The first camera is the master. GPO1 of the master has to be connected to GPI1 of all other
+// cameras.
 fn main() -> Result<(), XI_RETURN> {
     let num_devs = number_devices()?;
-    let mut acq_buffers = Vec::with_capacity(num_devs as usize);
+    let mut cameras = Vec::with_capacity(num_devs as usize);
     for i in 0..num_devs {
         let mut cam = open_device(Some(i))?;
-        cam.set_exposure(1000 as f32)?;
-        cam.set_trg_source(XI_TRG_SOFTWARE)?;
-        acq_buffers.push(cam.start_acquisition()?);
+        cam.set_exposure(1000.0)?;
+        cameras.push(cam);
     }
-    for buf in &mut acq_buffers {
-        buf.software_trigger()?;
+    if cameras.is_empty() {
+        return Ok(());
     }
-    for buf in &acq_buffers {
-        let img = buf.next_image::<u8>(None)?;
+    let master = cameras.remove(0);
+    let mut group = CameraGroup::new(master, cameras, SyncConfig::default())?;
+    group.master_mut().set_acq_timing_mode(XI_ACQ_TIMING_MODE_FRAME_RATE)?;
+    group.master_mut().set_framerate(10.0)?;
+    let mut acq = group.start_acquisition::<u8>()?;
+    for _ in 0..10 {
+        let frames = acq.next_frames(None)?;
+        let img = frames.master().image();
         println!(
-            "Received image! Width: {}, Height: {}",
+            "Received frame {}! Width: {}, Height: {}, missing partners: {:?}",
+            frames.frame_number(),
             img.width(),
-            img.height()
+            img.height(),
+            frames.missing()
         );
     }
+    acq.stop_acquisition()?;
     Ok(())
 }
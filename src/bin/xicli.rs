@@ -0,0 +1,268 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+//! Command line tool to inspect and configure XIMEA cameras.
+
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::process::ExitCode;
+
+use xiapi::Camera;
+use xiapi::ParamValue;
+use xiapi::XI_IMG_FORMAT;
+use xiapi::XI_RETURN;
+
+const USAGE: &str = "\
+Usage: xicli [--device <index> | --serial <serial number>] <command>
+
+Commands:
+    list                      List connected cameras
+    info                      Show camera model, versions and parameter ranges
+    get <param>               Read a parameter, e.g. \"exposure\" or \"gain:max\"
+    set <param> <value>       Change a parameter
+    capture <count> <prefix>  Save images as <prefix>_<n>.pgm or .ppm
+    config save <file>        Save all parameters to a file
+    config load <file>        Load parameters from a file";
+
+/// Version and model parameters shown by `info`
+const INFO_PARAMS: &[&str] = &[
+    "device_name",
+    "device_type",
+    "device_sn",
+    "device_model_id",
+    "sensor_model_id",
+    "device_sens_sn",
+    "hw_revision",
+    "api_version",
+    "drv_version",
+    "version_mcu1",
+    "version_mcu2",
+    "version_mcu3",
+    "version_fpga1",
+    "version_xmlman",
+    "factory_set_version",
+];
+
+/// Parameters whose value and range are shown by `info`
+const RANGE_PARAMS: &[&str] = &[
+    "exposure",
+    "gain",
+    "width",
+    "height",
+    "offsetX",
+    "offsetY",
+    "framerate",
+    "imgdataformat",
+    "downsampling",
+    "sensor_bit_depth",
+    "output_bit_depth",
+    "acq_buffer_size",
+    "trigger_source",
+    "limit_bandwidth",
+];
+
+/// Error of a command, either from xiAPI or from the command line
+enum CliError {
+    Usage(String),
+    Api(XI_RETURN),
+    Io(std::io::Error),
+}
+
+impl From<XI_RETURN> for CliError {
+    fn from(err: XI_RETURN) -> Self {
+        CliError::Api(err)
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(err: std::io::Error) -> Self {
+        CliError::Io(err)
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::Usage(message)) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+        Err(CliError::Api(err)) => {
+            eprintln!("xiAPI error {}", err);
+            ExitCode::FAILURE
+        }
+        Err(CliError::Io(err)) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), CliError> {
+    let mut device = None;
+    let mut serial = None;
+    let mut rest = args;
+    loop {
+        match rest {
+            [option, value, tail @ ..] if option == "--device" => {
+                let index = value
+                    .parse()
+                    .map_err(|_| CliError::Usage(format!("Invalid device index: {}", value)))?;
+                device = Some(index);
+                rest = tail;
+            }
+            [option, value, tail @ ..] if option == "--serial" => {
+                serial = Some(value.as_str());
+                rest = tail;
+            }
+            _ => break,
+        }
+    }
+    let open = || match serial {
+        Some(serial) => xiapi::open_device_by_serial(serial),
+        None => xiapi::open_device(device),
+    };
+    let rest: Vec<&str> = rest.iter().map(String::as_str).collect();
+    match rest.as_slice() {
+        ["list"] => list(),
+        ["info"] => info(&open()?),
+        ["get", name] => {
+            println!("{}", open()?.param_by_name(name)?);
+            Ok(())
+        }
+        ["set", name, value] => {
+            let value: ParamValue = value.parse().unwrap();
+            open()?.set_param_by_name(name, &value)?;
+            Ok(())
+        }
+        ["capture", count, prefix] => {
+            let count = count
+                .parse()
+                .map_err(|_| CliError::Usage(format!("Invalid image count: {}", count)))?;
+            capture(open()?, count, prefix)
+        }
+        ["config", "save", path] => {
+            fs::write(path, open()?.parameter_context()?)?;
+            Ok(())
+        }
+        ["config", "load", path] => {
+            let context = fs::read_to_string(path)?;
+            open()?.set_parameter_context(&context)?;
+            Ok(())
+        }
+        [] => Err(CliError::Usage("No command given".to_string())),
+        _ => Err(CliError::Usage(format!("Invalid command: {}", rest.join(" ")))),
+    }
+}
+
+fn list() -> Result<(), CliError> {
+    let count = xiapi::number_devices()?;
+    if count == 0 {
+        println!("No cameras found");
+    }
+    for dev_id in 0..count {
+        let name = xiapi::device_name(dev_id).unwrap_or_default();
+        let serial = xiapi::device_serial_number(dev_id).unwrap_or_default();
+        println!("{}: {} (SN {})", dev_id, name, serial);
+    }
+    Ok(())
+}
+
+fn info(cam: &Camera) -> Result<(), CliError> {
+    for name in INFO_PARAMS {
+        if let Ok(value) = cam.string_param_by_name(name) {
+            println!("{:<20} {}", name, value);
+        }
+    }
+    println!();
+    println!("{:<20} {:>12} {:>12} {:>12} {:>12}", "parameter", "value", "min", "max", "increment");
+    for name in RANGE_PARAMS {
+        let Ok(value) = cam.param_by_name(name) else {
+            continue;
+        };
+        let info = |modifier: &str| {
+            cam.param_by_name(&format!("{}{}", name, modifier))
+                .map_or("-".to_string(), |value| value.to_string())
+        };
+        println!(
+            "{:<20} {:>12} {:>12} {:>12} {:>12}",
+            name,
+            value.to_string(),
+            info(":min"),
+            info(":max"),
+            info(":inc")
+        );
+    }
+    Ok(())
+}
+
+fn capture(cam: Camera, count: u32, prefix: &str) -> Result<(), CliError> {
+    let format = cam.image_data_format()?;
+    // 16 bit formats hold the values of the sensor bit depth, which is the maximum value of PGM
+    let bit_depth = cam.image_data_bit_depth()?.clamp(1, 16);
+    let buffer = cam.start_acquisition()?;
+    for i in 0..count {
+        let path = match format {
+            XI_IMG_FORMAT::XI_MONO16 | XI_IMG_FORMAT::XI_RAW16 => {
+                let image = buffer.next_image::<u16>(None)?;
+                let path = format!("{}_{:04}.pgm", prefix, i);
+                let data = image.data();
+                let samples = rows(data, image.width(), 1, image.padding_x() as usize / 2);
+                let bytes: Vec<u8> = samples.flat_map(|value| value.to_be_bytes()).collect();
+                let max_value = ((1u32 << bit_depth) - 1) as u16;
+                write_pnm(&path, "P5", image.width(), image.height(), max_value, &bytes)?;
+                path
+            }
+            XI_IMG_FORMAT::XI_MONO8 | XI_IMG_FORMAT::XI_RAW8 => {
+                let image = buffer.next_image::<u8>(None)?;
+                let path = format!("{}_{:04}.pgm", prefix, i);
+                let data = image.data();
+                let bytes: Vec<u8> = rows(data, image.width(), 1, image.padding_x() as usize).collect();
+                write_pnm(&path, "P5", image.width(), image.height(), u8::MAX as u16, &bytes)?;
+                path
+            }
+            XI_IMG_FORMAT::XI_RGB24 | XI_IMG_FORMAT::XI_RGB32 => {
+                let channels = if format == XI_IMG_FORMAT::XI_RGB24 { 3 } else { 4 };
+                let image = buffer.next_image::<u8>(None)?;
+                let path = format!("{}_{:04}.ppm", prefix, i);
+                let data = image.data();
+                let pixels: Vec<u8> = rows(data, image.width(), channels, image.padding_x() as usize).collect();
+                // The camera delivers BGR(A), PPM expects RGB
+                let bytes: Vec<u8> = pixels
+                    .chunks_exact(channels)
+                    .flat_map(|bgr| [bgr[2], bgr[1], bgr[0]])
+                    .collect();
+                write_pnm(&path, "P6", image.width(), image.height(), u8::MAX as u16, &bytes)?;
+                path
+            }
+            _ => {
+                return Err(CliError::Usage(format!(
+                    "Image format {} can not be saved",
+                    format
+                )))
+            }
+        };
+        println!("{}", path);
+    }
+    buffer.stop_acquisition()?;
+    Ok(())
+}
+
+/// Iterate over the samples of all rows, skipping the padding at the end of each row.
+fn rows<T: Copy>(data: &[T], width: u32, channels: usize, padding: usize) -> impl Iterator<Item = T> + '_ {
+    let length = width as usize * channels;
+    data.chunks(length + padding)
+        .flat_map(move |row| row[..length.min(row.len())].iter().copied())
+}
+
+fn write_pnm(path: &str, magic: &str, width: u32, height: u32, max_value: u16, data: &[u8]) -> Result<(), CliError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write!(writer, "{}\n{} {}\n{}\n", magic, width, height, max_value)?;
+    writer.write_all(data)?;
+    writer.flush()?;
+    Ok(())
+}
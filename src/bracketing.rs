@@ -0,0 +1,290 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use xiapi_sys::*;
+
+use crate::AcquisitionBuffer;
+use crate::Image;
+use crate::OwnedImage;
+
+/// Exposure series that can be captured with [AcquisitionBuffer::capture_bracket].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExposureBracket {
+    /// Exposure times in microseconds. One frame is captured for each entry.
+    pub exposures: Vec<f32>,
+
+    /// Maximum difference in microseconds between the exposure time reported for an image and
+    /// the exposure time set on the camera.
+    pub tolerance_us: u32,
+
+    /// Maximum number of frames that are skipped while waiting for a new exposure time to take
+    /// effect.
+    pub max_skipped_frames: u32,
+}
+
+impl ExposureBracket {
+    /// Create an exposure series of `count` frames where each frame is exposed `factor` times
+    /// longer than the previous one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let bracket = xiapi::ExposureBracket::geometric(1000.0, 4.0, 3);
+    /// assert_eq!(bracket.exposures, vec![1000.0, 4000.0, 16000.0]);
+    /// ```
+    pub fn geometric(base_exposure: f32, factor: f32, count: usize) -> Self {
+        let exposures = (0..count)
+            .map(|i| base_exposure * factor.powi(i as i32))
+            .collect();
+        ExposureBracket {
+            exposures,
+            tolerance_us: 1,
+            max_skipped_frames: 10,
+        }
+    }
+}
+
+impl AcquisitionBuffer {
+    /// Capture one frame for each exposure time of an exposure series.
+    ///
+    /// Changing the exposure time during acquisition does not affect frames that are already
+    /// exposed or waiting in the buffer. Frames are therefore only accepted if the exposure time
+    /// reported in the image matches the exposure time set on the camera. Other frames are
+    /// skipped. If no matching frame arrives within `max_skipped_frames` frames, `XI_TIMEOUT` is
+    /// returned.
+    ///
+    /// The exposure time that was set before the series is restored afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// let cam = xiapi::open_device(None)?;
+    /// let mut buffer = cam.start_acquisition()?;
+    /// let bracket = xiapi::ExposureBracket::geometric(1000.0, 4.0, 3);
+    /// let frames = buffer.capture_bracket::<u8>(&bracket, None)?;
+    /// let hdr = xiapi::HdrImage::merge(&frames)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn capture_bracket<T: Clone>(
+        &mut self,
+        bracket: &ExposureBracket,
+        timeout: Option<u32>,
+    ) -> Result<Vec<OwnedImage<T>>, XI_RETURN> {
+        let previous_exposure = self.camera.exposure()?;
+        let frames = self.capture_exposures(bracket, timeout);
+        let restored = self.set_exposure(previous_exposure);
+        let frames = frames?;
+        restored?;
+        Ok(frames)
+    }
+
+    fn capture_exposures<T: Clone>(
+        &mut self,
+        bracket: &ExposureBracket,
+        timeout: Option<u32>,
+    ) -> Result<Vec<OwnedImage<T>>, XI_RETURN> {
+        let mut frames = Vec::with_capacity(bracket.exposures.len());
+        for &exposure in &bracket.exposures {
+            self.set_exposure(exposure)?;
+            // The camera may round the exposure time, so the actual value has to be used
+            let actual_exposure = self.camera.exposure()?;
+            let mut skipped_frames = 0;
+            loop {
+                let image = self.next_image::<T>(timeout)?;
+                let difference = (image.exposure_time_us() as f32 - actual_exposure).abs();
+                if difference <= bracket.tolerance_us as f32 {
+                    frames.push(image.to_owned_image());
+                    break;
+                }
+                if skipped_frames == bracket.max_skipped_frames {
+                    return Err(XI_RET::XI_TIMEOUT as XI_RETURN);
+                }
+                skipped_frames += 1;
+            }
+        }
+        Ok(frames)
+    }
+}
+
+/// Floating point image on a linear radiometric scale.
+///
+/// Pixel values are given in digital numbers per microsecond of exposure.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HdrImage {
+    width: u32,
+    height: u32,
+    format: XI_IMG_FORMAT::Type,
+    nb_channels: usize,
+    data: Vec<f32>,
+}
+
+impl HdrImage {
+    /// Merge an exposure series into a single HDR image.
+    ///
+    /// Every pixel is the weighted average of the exposure normalized values of all frames.
+    /// Values close to black or to saturation get a low weight. If a pixel is saturated in every
+    /// frame, the value from the shortest exposure is used. If it is black in every frame, the
+    /// value from the longest exposure is used.
+    ///
+    /// All frames must have the same size and format. Returns `XI_INVALID_ARG` otherwise, and
+    /// `XI_NOT_SUPPORTED_DATA_FORMAT` for planar formats, packed transport data, unknown formats
+    /// and frames without image data.
+    pub fn merge<T>(frames: &[OwnedImage<T>]) -> Result<HdrImage, XI_RETURN>
+    where
+        T: Copy + Into<f32>,
+    {
+        let images: Vec<Image<T>> = frames.iter().map(|frame| frame.image()).collect();
+        let first = images.first().ok_or(XI_RET::XI_INVALID_ARG as XI_RETURN)?;
+        let compatible = images.iter().all(|image| {
+            image.width() == first.width()
+                && image.height() == first.height()
+                && image.format() == first.format()
+                && image.exposure_time_us() > 0
+        });
+        if !compatible {
+            return Err(XI_RET::XI_INVALID_ARG as XI_RETURN);
+        }
+        if first.is_planar()
+            || first.nb_channels() == 0
+            || first.format() == XI_IMG_FORMAT::XI_FRM_TRANSPORT_DATA
+        {
+            return Err(XI_RET::XI_NOT_SUPPORTED_DATA_FORMAT as XI_RETURN);
+        }
+
+        let shortest = (0..images.len())
+            .min_by_key(|&i| images[i].exposure_time_us())
+            .unwrap_or(0);
+        let longest = (0..images.len())
+            .max_by_key(|&i| images[i].exposure_time_us())
+            .unwrap_or(0);
+
+        let nb_channels = first.nb_channels();
+        let row_length = first.width() as usize * nb_channels;
+        let mut data = Vec::with_capacity(row_length * first.height() as usize);
+        for y in 0..first.height() as usize {
+            let rows: Vec<&[T]> = images
+                .iter()
+                .map(|image| image.row(y))
+                .collect::<Option<_>>()
+                .ok_or(XI_RET::XI_NOT_SUPPORTED_DATA_FORMAT as XI_RETURN)?;
+            for x in 0..row_length {
+                let mut weighted_sum = 0.0;
+                let mut weight_sum = 0.0;
+                let mut fallback = None;
+                for (i, (image, row)) in images.iter().zip(&rows).enumerate() {
+                    let black_level = image.black_level() as f32;
+                    let saturation = image.saturation_level() - black_level;
+                    let value = (row[x].into() - black_level).clamp(0.0, saturation);
+                    let radiance = value / image.exposure_time_us() as f32;
+                    let weight = value.min(saturation - value) / saturation;
+                    weighted_sum += weight * radiance;
+                    weight_sum += weight;
+                    let bright = value > saturation / 2.0;
+                    if (bright && i == shortest) || (!bright && i == longest) {
+                        fallback = Some(radiance);
+                    }
+                }
+                data.push(match weight_sum > 0.0 {
+                    true => weighted_sum / weight_sum,
+                    false => fallback.unwrap_or(0.0),
+                });
+            }
+        }
+        Ok(HdrImage {
+            width: first.width(),
+            height: first.height(),
+            format: first.format(),
+            nb_channels,
+            data,
+        })
+    }
+
+    /// Get the width of this image in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of this image
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Number of values per pixel
+    pub fn nb_channels(&self) -> usize {
+        self.nb_channels
+    }
+
+    /// Get all channels of a pixel
+    pub fn pixel(&self, x: usize, y: usize) -> Option<&[f32]> {
+        if x >= self.width as usize || y >= self.height as usize {
+            return None;
+        }
+        let offset = (y * self.width as usize + x) * self.nb_channels;
+        self.data.get(offset..offset + self.nb_channels)
+    }
+
+    /// Get the image data as a slice (without padding).
+    pub fn data(&self) -> &[f32] {
+        &self.data
+    }
+
+    /// Compress the dynamic range to 8 bits per channel using Reinhard's global operator.
+    ///
+    /// The image is scaled so that its log-average luminance is mapped to `key` (0.18 is a
+    /// common value) before compression.
+    /// For color images, the luminance is computed from the BGR channel order used by xiAPI and
+    /// all color channels are scaled by the same factor to preserve the hue.
+    ///
+    /// Returns `XI_NOT_SUPPORTED_DATA_FORMAT` if the channels have no 8 bit format.
+    pub fn tone_map(&self, key: f32) -> Result<OwnedImage<u8>, XI_RETURN> {
+        if self.nb_channels == 0 {
+            return Err(XI_RET::XI_NOT_SUPPORTED_DATA_FORMAT as XI_RETURN);
+        }
+        let luminances: Vec<f32> = self
+            .data
+            .chunks_exact(self.nb_channels)
+            .map(luminance)
+            .collect();
+        let log_sum: f32 = luminances.iter().map(|l| (l + 1e-6).ln()).sum();
+        let log_average = (log_sum / luminances.len().max(1) as f32).exp();
+
+        let mut data = Vec::with_capacity(self.data.len());
+        for (pixel, &lum) in self.data.chunks_exact(self.nb_channels).zip(&luminances) {
+            let scaled = key / log_average * lum;
+            let mapped = scaled / (1.0 + scaled);
+            let factor = match lum > 0.0 {
+                true => mapped / lum,
+                false => 0.0,
+            };
+            for (channel, &value) in pixel.iter().enumerate() {
+                data.push(match channel {
+                    // The fourth channel of RGB32 is not a color
+                    3 => u8::MAX,
+                    _ => (value * factor * 255.0).round().clamp(0.0, 255.0) as u8,
+                });
+            }
+        }
+        let format = match self.format {
+            XI_IMG_FORMAT::XI_RAW8 | XI_IMG_FORMAT::XI_RAW16 => XI_IMG_FORMAT::XI_RAW8,
+            _ => match self.nb_channels {
+                3 => XI_IMG_FORMAT::XI_RGB24,
+                4 => XI_IMG_FORMAT::XI_RGB32,
+                _ => XI_IMG_FORMAT::XI_MONO8,
+            },
+        };
+        OwnedImage::new(self.width, self.height, format, data)
+            .ok_or(XI_RET::XI_NOT_SUPPORTED_DATA_FORMAT as XI_RETURN)
+    }
+}
+
+fn luminance(pixel: &[f32]) -> f32 {
+    match pixel {
+        [b, g, r, ..] => 0.0722 * b + 0.7152 * g + 0.2126 * r,
+        [value, ..] => *value,
+        [] => 0.0,
+    }
+}
@@ -0,0 +1,405 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+
+use xiapi_sys::*;
+
+use crate::tiff::cfa_pattern;
+use crate::tiff::write_gray_tiff;
+use crate::AcquisitionBuffer;
+use crate::Camera;
+use crate::Image;
+use crate::OwnedImage;
+use crate::PixelValue;
+
+/// Average of several frames, used as dark or flat reference for flat field correction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationFrame {
+    width: u32,
+    height: u32,
+    nb_channels: usize,
+    bayer: bool,
+    saturation: f32,
+    data: Vec<f32>,
+}
+
+/// Limits used to check calibration frames before they are used.
+///
+/// All levels are relative to the saturation level of the camera.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationLimits {
+    /// Maximum mean level of a dark frame
+    pub max_dark_level: f32,
+
+    /// Minimum mean level of a flat frame
+    pub min_flat_level: f32,
+
+    /// Maximum mean level of a flat frame
+    pub max_flat_level: f32,
+
+    /// Maximum fraction of saturated pixels in a flat frame
+    pub max_saturated_fraction: f32,
+
+    /// Minimum ratio of the darkest pixels of the flat frame to the mean value of the pixels of
+    /// the same color. Pixels darker than that can not be corrected without amplifying noise
+    /// too much.
+    pub min_flat_uniformity: f32,
+
+    /// Percentage of the darkest pixels of every color that is ignored by the uniformity check,
+    /// so that single defective pixels do not fail the calibration.
+    pub uniformity_percentile: f32,
+}
+
+impl Default for CalibrationLimits {
+    fn default() -> Self {
+        CalibrationLimits {
+            max_dark_level: 0.1,
+            min_flat_level: 0.2,
+            max_flat_level: 0.9,
+            max_saturated_fraction: 0.0001,
+            min_flat_uniformity: 0.5,
+            uniformity_percentile: 0.1,
+        }
+    }
+}
+
+impl CalibrationFrame {
+    /// Get the width of this frame in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of this frame
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Number of values per pixel
+    pub fn nb_channels(&self) -> usize {
+        self.nb_channels
+    }
+
+    /// Check if the frame is a RAW image with a Bayer color filter
+    pub fn is_bayer(&self) -> bool {
+        self.bayer
+    }
+
+    /// Value at which the sensor saturates
+    pub fn saturation(&self) -> f32 {
+        self.saturation
+    }
+
+    /// Averaged pixel values, row by row without padding
+    pub fn data(&self) -> &[f32] {
+        &self.data
+    }
+
+    /// Mean value of all pixels
+    pub fn mean(&self) -> f32 {
+        self.data.iter().sum::<f32>() / self.data.len().max(1) as f32
+    }
+
+    /// Number of planes of pixels with the same color: the channels, or the four positions of
+    /// the 2x2 pattern of a Bayer filter.
+    pub(crate) fn nb_planes(&self) -> usize {
+        match self.bayer {
+            true => 4,
+            false => self.nb_channels.max(1),
+        }
+    }
+
+    /// Plane of the value at the given index of [Self::data()]
+    pub(crate) fn plane(&self, index: usize) -> usize {
+        match self.bayer {
+            true => {
+                let width = self.width.max(1) as usize;
+                (index / width % 2) * 2 + index % width % 2
+            }
+            false => index % self.nb_channels.max(1),
+        }
+    }
+
+    /// Mean value of every plane, see [Self::nb_planes()]
+    pub(crate) fn plane_means(&self) -> Vec<f32> {
+        let mut sums = vec![0.0f64; self.nb_planes()];
+        let mut counts = vec![0usize; self.nb_planes()];
+        for (i, &value) in self.data.iter().enumerate() {
+            let plane = self.plane(i);
+            sums[plane] += value as f64;
+            counts[plane] += 1;
+        }
+        sums.iter()
+            .zip(counts)
+            .map(|(&sum, count)| (sum / count.max(1) as f64) as f32)
+            .collect()
+    }
+
+    /// Fraction of pixels that are saturated
+    pub fn saturated_fraction(&self) -> f32 {
+        let saturated = self.data.iter().filter(|&&v| v >= self.saturation).count();
+        saturated as f32 / self.data.len().max(1) as f32
+    }
+
+    /// Check that this frame is dark enough to be used as dark reference.
+    ///
+    /// Returns `XI_SHADING_TOOBRIGHT` otherwise.
+    pub fn validate_dark(&self, limits: &CalibrationLimits) -> Result<(), XI_RETURN> {
+        match self.mean() > limits.max_dark_level * self.saturation {
+            true => Err(XI_RET::XI_SHADING_TOOBRIGHT as XI_RETURN),
+            false => Ok(()),
+        }
+    }
+
+    /// Check that this frame can be used as flat reference.
+    ///
+    /// Returns `XI_SHADING_TOODARK` or `XI_SHADING_TOOBRIGHT` if the mean level or the number of
+    /// saturated pixels is out of range and `XI_SHADING_TOOCONTRAST` if the illumination is not
+    /// uniform enough. Uniformity is checked for every channel, or for every position of the 2x2
+    /// pattern of a Bayer filter, with the [CalibrationLimits::uniformity_percentile] of its
+    /// values.
+    pub fn validate_flat(&self, limits: &CalibrationLimits) -> Result<(), XI_RETURN> {
+        let mean = self.mean();
+        if mean < limits.min_flat_level * self.saturation {
+            return Err(XI_RET::XI_SHADING_TOODARK as XI_RETURN);
+        }
+        if mean > limits.max_flat_level * self.saturation
+            || self.saturated_fraction() > limits.max_saturated_fraction
+        {
+            return Err(XI_RET::XI_SHADING_TOOBRIGHT as XI_RETURN);
+        }
+        let mut planes = vec![Vec::new(); self.nb_planes()];
+        for (i, &value) in self.data.iter().enumerate() {
+            planes[self.plane(i)].push(value);
+        }
+        for (mut values, plane_mean) in planes.into_iter().zip(self.plane_means()) {
+            if values.is_empty() {
+                continue;
+            }
+            let percentile = limits.uniformity_percentile.clamp(0.0, 100.0) / 100.0;
+            let rank = ((percentile * values.len() as f32) as usize).min(values.len() - 1);
+            let (_, &mut dark, _) = values.select_nth_unstable_by(rank, f32::total_cmp);
+            if dark < limits.min_flat_uniformity * plane_mean {
+                return Err(XI_RET::XI_SHADING_TOOCONTRAST as XI_RETURN);
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the frame as uncompressed grayscale TIFF file as used for xiAPI flat field
+    /// correction.
+    ///
+    /// Values are rounded to 8 bits if the saturation level of the camera fits into 8 bits,
+    /// otherwise 16 bits are used.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let width = self.width * self.nb_channels as u32;
+        match self.saturation <= u8::MAX as f32 {
+            true => {
+                let data: Vec<u8> = self.data.iter().map(|&v| u8::from_f32(v)).collect();
+                write_gray_tiff(&mut writer, width, self.height, 8, &data)?;
+            }
+            false => {
+                let data: Vec<u8> = self
+                    .data
+                    .iter()
+                    .flat_map(|&v| u16::from_f32(v).to_le_bytes())
+                    .collect();
+                write_gray_tiff(&mut writer, width, self.height, 16, &data)?;
+            }
+        }
+        writer.flush()
+    }
+}
+
+/// Planar images are converted to the interleaved format first.
+impl<T: PixelValue> From<&Image<'_, T>> for CalibrationFrame {
+    fn from(image: &Image<T>) -> Self {
+        if image.is_planar() {
+            return Self::from(&image.to_interleaved().image());
+        }
+        let mut frame = CalibrationFrame {
+            width: image.width(),
+            height: image.height(),
+            nb_channels: image.nb_channels(),
+            bayer: image.nb_channels() == 1
+                && cfa_pattern(image.color_filter_array()).is_some()
+                && matches!(image.format(), XI_IMG_FORMAT::XI_RAW8 | XI_IMG_FORMAT::XI_RAW16),
+            saturation: image.saturation_level(),
+            data: Vec::with_capacity(image.width() as usize * image.height() as usize),
+        };
+        for y in 0..image.height() as usize {
+            if let Some(row) = image.row(y) {
+                frame.data.extend(row.iter().map(|v| v.to_f32()));
+            }
+        }
+        frame
+    }
+}
+
+impl AcquisitionBuffer {
+    /// Capture `count` frames and average them into a single calibration frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// let cam = xiapi::open_device(None)?;
+    /// let buffer = cam.start_acquisition()?;
+    /// // Cover the lens
+    /// let dark = buffer.capture_average::<u8>(16, None)?;
+    /// // Point the camera at a uniformly lit target
+    /// let flat = buffer.capture_average::<u8>(16, None)?;
+    /// let correction = xiapi::FlatFieldCorrection::new(dark, flat, &Default::default())?;
+    /// let mut cam = buffer.stop_acquisition()?;
+    /// correction.save("dark.tif", "flat.tif").expect("Could not save calibration files");
+    /// cam.enable_ffc("dark.tif", "flat.tif")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn capture_average<T: PixelValue>(
+        &self,
+        count: usize,
+        timeout: Option<u32>,
+    ) -> Result<CalibrationFrame, XI_RETURN> {
+        if count == 0 {
+            return Err(XI_RET::XI_INVALID_ARG as XI_RETURN);
+        }
+        let mut average = CalibrationFrame::from(&self.next_image::<T>(timeout)?);
+        for _ in 1..count {
+            let frame = CalibrationFrame::from(&self.next_image::<T>(timeout)?);
+            if frame.data.len() != average.data.len() {
+                return Err(XI_RET::XI_INVALID_DATA as XI_RETURN);
+            }
+            for (sum, value) in average.data.iter_mut().zip(frame.data) {
+                *sum += value;
+            }
+        }
+        for value in average.data.iter_mut() {
+            *value /= count as f32;
+        }
+        Ok(average)
+    }
+}
+
+/// Dark and flat reference frames for flat field correction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatFieldCorrection {
+    dark: CalibrationFrame,
+    flat: CalibrationFrame,
+    channel_means: Vec<f32>,
+}
+
+impl FlatFieldCorrection {
+    /// Create a flat field correction from a dark and a flat frame.
+    ///
+    /// Both frames are checked against the given limits. Returns `XI_INVALID_ARG` if their sizes
+    /// do not match.
+    pub fn new(
+        dark: CalibrationFrame,
+        flat: CalibrationFrame,
+        limits: &CalibrationLimits,
+    ) -> Result<Self, XI_RETURN> {
+        if dark.width != flat.width
+            || dark.height != flat.height
+            || dark.nb_channels != flat.nb_channels
+        {
+            return Err(XI_RET::XI_INVALID_ARG as XI_RETURN);
+        }
+        dark.validate_dark(limits)?;
+        flat.validate_flat(limits)?;
+
+        // Every channel is normalized separately to keep the white balance
+        let nb_channels = flat.nb_channels;
+        let mut channel_means = vec![0.0; nb_channels];
+        for (i, (f, d)) in flat.data.iter().zip(&dark.data).enumerate() {
+            channel_means[i % nb_channels] += f - d;
+        }
+        let pixels = (flat.data.len() / nb_channels).max(1) as f32;
+        for mean in channel_means.iter_mut() {
+            *mean /= pixels;
+        }
+        Ok(FlatFieldCorrection {
+            dark,
+            flat,
+            channel_means,
+        })
+    }
+
+    /// Dark reference frame
+    pub fn dark(&self) -> &CalibrationFrame {
+        &self.dark
+    }
+
+    /// Flat reference frame
+    pub fn flat(&self) -> &CalibrationFrame {
+        &self.flat
+    }
+
+    /// Write the dark and flat frames to the files used by [Camera::enable_ffc].
+    pub fn save<P: AsRef<Path>>(&self, dark_path: P, flat_path: P) -> io::Result<()> {
+        self.dark.save(dark_path)?;
+        self.flat.save(flat_path)
+    }
+
+    /// Apply the correction to an image on the host.
+    ///
+    /// This is the same correction the camera applies when FFC is enabled and can be used for
+    /// images that were captured without it:
+    /// `corrected = (raw - dark) * mean(flat - dark) / (flat - dark)`
+    ///
+    /// Returns `XI_INVALID_ARG` if the size of the image does not match the reference frames and
+    /// `XI_NOT_SUPPORTED_DATA_FORMAT` for planar formats.
+    pub fn apply<T: PixelValue>(&self, image: &Image<T>) -> Result<OwnedImage<T>, XI_RETURN> {
+        if image.is_planar() {
+            return Err(XI_RET::XI_NOT_SUPPORTED_DATA_FORMAT as XI_RETURN);
+        }
+        if image.width() != self.flat.width
+            || image.height() != self.flat.height
+            || image.nb_channels() != self.flat.nb_channels
+        {
+            return Err(XI_RET::XI_INVALID_ARG as XI_RETURN);
+        }
+        let nb_channels = self.flat.nb_channels;
+        let row_length = self.flat.width as usize * nb_channels;
+        let mut corrected = image.to_owned_image();
+        for y in 0..self.flat.height as usize {
+            let dark = &self.dark.data[y * row_length..(y + 1) * row_length];
+            let flat = &self.flat.data[y * row_length..(y + 1) * row_length];
+            if let Some(row) = corrected.row_mut(y) {
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    let gain = match flat[x] - dark[x] {
+                        d if d > 0.0 => self.channel_means[x % nb_channels] / d,
+                        _ => 1.0,
+                    };
+                    *pixel = T::from_f32((pixel.to_f32() - dark[x]) * gain);
+                }
+            }
+        }
+        Ok(corrected)
+    }
+}
+
+impl Camera {
+    /// Enable flat field correction in xiAPI with the given dark and flat reference files.
+    ///
+    /// The files can be created with [FlatFieldCorrection::save].
+    pub fn enable_ffc(&mut self, dark_file: &str, flat_file: &str) -> Result<(), XI_RETURN> {
+        unsafe {
+            self.set_param_string(XI_PRM_FFC_DARK_FIELD_FILE_NAME, dark_file)?;
+            self.set_param_string(XI_PRM_FFC_FLAT_FIELD_FILE_NAME, flat_file)?;
+            self.set_param(XI_PRM_FFC, XI_SWITCH::XI_ON)
+        }
+    }
+
+    /// Disable flat field correction in xiAPI.
+    pub fn disable_ffc(&mut self) -> Result<(), XI_RETURN> {
+        unsafe { self.set_param(XI_PRM_FFC, XI_SWITCH::XI_OFF) }
+    }
+}
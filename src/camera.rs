@@ -2,6 +2,7 @@
  * Copyright (c) 2022. XIMEA GmbH - All Rights Reserved
  */
 use std::ffi::CStr;
+use std::ffi::CString;
 use std::marker::PhantomData;
 use std::mem::size_of;
 use std::mem::MaybeUninit;
@@ -87,6 +88,30 @@ macro_rules! param {
         }
     };
 }
+
+/// This macro is used to forward setters of parameters that can be changed during acquisition from
+/// the [AcquisitionBuffer] to the [Camera].
+/// The parameters are specified using the following syntax: <ParamName>: <Type>
+/// Documentation on the parameter will be added to the setter.
+macro_rules! runtime_param {
+    () => {};
+    (
+        $(#[doc = $doc:expr])*
+        $prm:ident : $type:ty;
+        $($tail:tt)*
+    ) => {
+        paste! {
+            #[doc = "Set the `" $prm "` parameter during acquisition. See also [Camera::" $prm "()]"]
+            ///
+            $(#[doc = $doc])*
+            pub fn [<set_ $prm>](&mut self, value: $type) -> Result<(), XI_RETURN> {
+                self.camera.[<set_ $prm>](value)
+            }
+            runtime_param!($($tail)*);
+        }
+    };
+}
+
 /// Connected and initialized XIMEA camera.
 ///
 /// Must be mutable to allow changing any parameters. A non-mutable Camera can be used from
@@ -108,7 +133,7 @@ pub struct Camera {
 /// Trying to change an parameter that is not changeable during acquisition is therefore an error at
 /// compile time (as opposed to runtime in C/C++).
 pub struct AcquisitionBuffer {
-    camera: Camera,
+    pub(crate) camera: Camera,
 }
 
 /// Initializes a camera and returns it.
@@ -126,7 +151,7 @@ pub struct AcquisitionBuffer {
 /// # Arguments
 ///
 /// * `dev_id`: The device ID for the device to be initialized. Usually device IDs are sequential
-/// and start at 0 for the first device in the system. Default value: 0
+///   and start at 0 for the first device in the system. Default value: 0
 ///
 /// # Examples
 ///
@@ -160,7 +185,7 @@ pub fn open_device(dev_id: Option<u32>) -> Result<Camera, XI_RETURN> {
 /// # Arguments
 ///
 /// *`dev_id`: The device ID for the device to be initialized. Usually device IDs are sequential
-/// and start at 0 for the first device in the system. Default value: 0
+///   and start at 0 for the first device in the system. Default value: 0
 /// *`bandwidth`: Transport layer bandwidth for this camera in MBit/s
 ///
 /// # Examples
@@ -214,6 +239,33 @@ pub fn open_device_manual_bandwidth(
     }
 }
 
+/// Initializes the camera with the given serial number and returns it.
+///
+/// Unlike device IDs, serial numbers do not change when cameras are connected in a different
+/// order.
+///
+/// # Examples
+///
+/// ```
+/// # #[serial_test::file_serial]
+/// # fn main() -> Result<(), xiapi_sys::XI_RETURN>{
+///     let mut cam = xiapi::open_device_by_serial("12345678")?;
+///     cam.set_exposure(10000.0)?;
+/// #   Ok(())
+/// # }
+/// ```
+pub fn open_device_by_serial(serial_number: &str) -> Result<Camera, XI_RETURN> {
+    let serial_c = CString::new(serial_number).or(Err(XI_RET::XI_INVALID_ARG as XI_RETURN))?;
+    let mut device_handle: HANDLE = std::ptr::null_mut();
+    let err = unsafe {
+        xiapi_sys::xiOpenDeviceBy(XI_OPEN_BY::XI_OPEN_BY_SN, serial_c.as_ptr(), &mut device_handle)
+    };
+    match err as XI_RET::Type {
+        XI_RET::XI_OK => Ok(Camera { device_handle }),
+        _ => Err(err),
+    }
+}
+
 /// Returns the number of available cameras.
 ///
 /// # Examples
@@ -239,15 +291,60 @@ pub fn number_devices() -> Result<u32, XI_RETURN> {
     }
 }
 
+/// Read the model name of a camera without opening it.
+///
+/// # Examples
+///
+/// ```
+/// # #[serial_test::file_serial]
+/// # fn main() -> Result<(), xiapi::XI_RETURN>{
+///     for i in 0..xiapi::number_devices()? {
+///         println!("{}: {}", i, xiapi::device_name(i)?);
+///     }
+/// # Ok(())
+/// # }
+/// ```
+pub fn device_name(dev_id: u32) -> Result<String, XI_RETURN> {
+    device_info_string(dev_id, XI_PRM_DEVICE_NAME)
+}
+
+/// Read a string parameter of a camera without opening it.
+pub(crate) fn device_info_string(dev_id: u32, param: &[u8]) -> Result<String, XI_RETURN> {
+    let param_c = CStr::from_bytes_with_nul(param).or(Err(XI_RET::XI_INVALID_ARG as XI_RETURN))?;
+    let mut buffer = [0u8; 256];
+    let err = unsafe {
+        xiapi_sys::xiGetDeviceInfoString(
+            dev_id,
+            param_c.as_ptr(),
+            buffer.as_mut_ptr() as *mut c_char,
+            buffer.len() as DWORD,
+        )
+    };
+    if err as XI_RET::Type != XI_RET::XI_OK {
+        return Err(err);
+    }
+    let length = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    String::from_utf8(buffer[..length].to_vec()).or(Err(XI_RET::XI_INVALID_DATA as XI_RETURN))
+}
+
+impl Camera {
+    /// Close the device handle, e.g. to open the camera again. Dropping the camera afterwards
+    /// does nothing.
+    pub(crate) fn close(&mut self) {
+        if !self.device_handle.is_null() {
+            unsafe { xiapi_sys::xiCloseDevice(self.device_handle) };
+            self.device_handle = std::ptr::null_mut();
+        }
+    }
+}
+
 impl Drop for Camera {
     fn drop(&mut self) {
-        unsafe {
-            xiapi_sys::xiCloseDevice(self.device_handle);
-        }
+        self.close();
     }
 }
 
-trait ParamType: Default {
+pub(crate) trait ParamType: Default {
     unsafe fn get_param(
         handle: xiapi_sys::HANDLE,
         prm: *const std::os::raw::c_char,
@@ -295,7 +392,7 @@ impl ParamType for u64 {
     // Selectors in xiAPI are defined as unsigned int, but treated as if they were signed
     unsafe fn get_param(handle: HANDLE, prm: *const c_char, value: &mut Self) -> XI_RETURN {
         let mut size: DWORD = std::mem::size_of::<Self>() as DWORD;
-        let mut xi_type_integer64: u32 = XI_PRM_TYPE::xiTypeInteger64 as u32;
+        let mut xi_type_integer64: u32 = XI_PRM_TYPE::xiTypeInteger64;
         xiapi_sys::xiGetParam(
             handle,
             prm,
@@ -342,7 +439,7 @@ impl Camera {
         }
     }
 
-    unsafe fn set_param<T: ParamType>(&mut self, param: &[u8], value: T) -> Result<(), XI_RETURN> {
+    pub(crate) unsafe fn set_param<T: ParamType>(&mut self, param: &[u8], value: T) -> Result<(), XI_RETURN> {
         let param_c = match CStr::from_bytes_with_nul(param) {
             Ok(c) => c,
             Err(_) => return Err(XI_RET::XI_INVALID_ARG as XI_RETURN),
@@ -354,7 +451,7 @@ impl Camera {
         }
     }
 
-    unsafe fn param<T: ParamType>(&self, param: &[u8]) -> Result<T, XI_RETURN> {
+    pub(crate) unsafe fn param<T: ParamType>(&self, param: &[u8]) -> Result<T, XI_RETURN> {
         let mut value = T::default();
         let param_c = match CStr::from_bytes_with_nul(param) {
             Ok(c) => c,
@@ -367,15 +464,71 @@ impl Camera {
         }
     }
 
+    pub(crate) unsafe fn set_param_bytes(&mut self, param: &[u8], value: &[u8]) -> Result<(), XI_RETURN> {
+        let param_c = match CStr::from_bytes_with_nul(param) {
+            Ok(c) => c,
+            Err(_) => return Err(XI_RET::XI_INVALID_ARG as XI_RETURN),
+        };
+        let err = xiapi_sys::xiSetParam(
+            self.device_handle,
+            param_c.as_ptr(),
+            value.as_ptr() as *mut std::os::raw::c_void,
+            value.len() as DWORD,
+            XI_PRM_TYPE::xiTypeString,
+        );
+        match err as XI_RET::Type {
+            XI_RET::XI_OK => Ok(()),
+            _ => Err(err),
+        }
+    }
+
+    /// Read a binary or string parameter into the buffer and return the number of bytes read.
+    pub(crate) unsafe fn param_bytes(&self, param: &[u8], buffer: &mut [u8]) -> Result<usize, XI_RETURN> {
+        let param_c = match CStr::from_bytes_with_nul(param) {
+            Ok(c) => c,
+            Err(_) => return Err(XI_RET::XI_INVALID_ARG as XI_RETURN),
+        };
+        let mut size = buffer.len() as DWORD;
+        let mut param_type = XI_PRM_TYPE::xiTypeString;
+        let err = xiapi_sys::xiGetParam(
+            self.device_handle,
+            param_c.as_ptr(),
+            buffer.as_mut_ptr() as *mut std::os::raw::c_void,
+            &mut size,
+            &mut param_type,
+        );
+        match err as XI_RET::Type {
+            XI_RET::XI_OK => Ok((size as usize).min(buffer.len())),
+            _ => Err(err),
+        }
+    }
+
+    pub(crate) unsafe fn set_param_string(&mut self, param: &[u8], value: &str) -> Result<(), XI_RETURN> {
+        let value_c = CString::new(value).or(Err(XI_RET::XI_INVALID_ARG as XI_RETURN))?;
+        self.set_param_bytes(param, value_c.as_bytes_with_nul())
+    }
+
+    pub(crate) unsafe fn param_string(&self, param: &[u8]) -> Result<String, XI_RETURN> {
+        self.param_string_sized(param, 1024)
+    }
+
+    pub(crate) unsafe fn param_string_sized(&self, param: &[u8], capacity: usize) -> Result<String, XI_RETURN> {
+        let mut buffer = vec![0u8; capacity];
+        self.param_bytes(param, &mut buffer)?;
+        let length = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+        buffer.truncate(length);
+        String::from_utf8(buffer).or(Err(XI_RET::XI_INVALID_DATA as XI_RETURN))
+    }
+
     unsafe fn param_increment<T: ParamType>(&self, param: &'static [u8]) -> Result<T, XI_RETURN> {
         self.param_info(param, XI_PRM_INFO_INCREMENT)
     }
 
-    unsafe fn param_min<T: ParamType>(&self, param: &'static [u8]) -> Result<T, XI_RETURN> {
+    pub(crate) unsafe fn param_min<T: ParamType>(&self, param: &'static [u8]) -> Result<T, XI_RETURN> {
         self.param_info(param, XI_PRM_INFO_MIN)
     }
 
-    unsafe fn param_max<T: ParamType>(&self, param: &'static [u8]) -> Result<T, XI_RETURN> {
+    pub(crate) unsafe fn param_max<T: ParamType>(&self, param: &'static [u8]) -> Result<T, XI_RETURN> {
         self.param_info(param, XI_PRM_INFO_MAX)
     }
 
@@ -461,17 +614,45 @@ impl Camera {
         Ok(result)
     }
 
+    /// Serial number of the camera
+    pub fn serial_number(&self) -> Result<String, XI_RETURN> {
+        unsafe { self.param_string(XI_PRM_DEVICE_SN) }
+    }
+
     /// Convenience method to read counters from the camera with a single call
     /// See also [Self.counter_selector] and [Self.counter_value]
     pub fn counter(
         &mut self,
         counter_selector: XI_COUNTER_SELECTOR::Type,
     ) -> Result<i32, XI_RETURN> {
-        let prev_selector = self.counter_selector()?;
-        self.set_counter_selector(counter_selector)?;
-        let result = self.counter_value()?;
-        self.set_counter_selector(prev_selector)?;
-        Ok(result)
+        self.with_selector(XI_PRM_COUNTER_SELECTOR, counter_selector, |cam| {
+            cam.counter_value()
+        })
+    }
+
+    /// Load the given user set and make it active.
+    ///
+    /// This changes the [Self::user_set_selector()] to the loaded user set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// use xiapi::XI_USER_SET_SELECTOR::XI_US_12_STD_L;
+    /// let mut cam = xiapi::open_device(None)?;
+    /// cam.load_user_set(XI_US_12_STD_L)?;
+    /// // Apply the same user set automatically after the next power cycle
+    /// cam.set_user_set_default(XI_US_12_STD_L)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load_user_set(
+        &mut self,
+        user_set: XI_USER_SET_SELECTOR::Type,
+    ) -> Result<(), XI_RETURN> {
+        self.set_user_set_selector(user_set)?;
+        unsafe { self.set_param(XI_PRM_USER_SET_LOAD, XI_SWITCH::XI_ON) }
     }
 
     /// Set the size of the acquisition buffer in bytes.
@@ -522,6 +703,9 @@ impl Camera {
         /// Current exposure time in microseconds.
         mut exposure: f32;
 
+        /// Exposure time group that is accessed by [Self::exposure()] and [Self::set_exposure()]
+        mut exposure_time_selector: XI_EXPOSURE_TIME_SELECTOR_TYPE::Type;
+
         /// Sets the number of times of exposure in one frame.
         mut exposure_burst_count: i32;
 
@@ -581,6 +765,9 @@ impl Camera {
         /// Selects the type of trigger overlap
         mut trg_overlap: XI_TRG_OVERLAP::Type;
 
+        /// Delay between the trigger signal and the start of the exposure in microseconds
+        mut trg_delay: i32;
+
         /// Sets the number of frames to be triggered for each trigger signal.
         /// This setting is only valid if the trigger selector is set to XI_TRG_SEL_FRAME_BURST_START
         mut acq_frame_burst_count: u32;
@@ -597,6 +784,9 @@ impl Camera {
         /// Defines functionality for the selected GPI
         mut gpi_mode: XI_GPI_MODE::Type;
 
+        /// Current level of the selected GPI
+        gpi_level: i32;
+
         /// Selects a GPO
         mut gpo_selector: XI_GPO_SELECTOR::Type;
 
@@ -640,6 +830,15 @@ impl Camera {
         /// Bit depth of the image returned by [Self::next_image()]
         mut image_data_bit_depth: XI_BIT_DEPTH::Type;
 
+        /// Enable packing of the pixel data on the transport layer
+        mut output_data_packing: XI_SWITCH::Type;
+
+        /// Packing scheme used if [Self::output_data_packing()] is enabled
+        mut output_data_packing_type: XI_OUTPUT_DATA_PACKING_TYPE::Type;
+
+        /// Pixel format on the transport layer as GenTL pixel format
+        mut transport_pixel_format: XI_GenTL_Image_Format_e::Type;
+
         /// Enable column fpn correction in camera
         mut column_fpn_correction: XI_SWITCH::Type;
 
@@ -652,6 +851,18 @@ impl Camera {
         /// Enable row black offset correction
         mut row_black_offset_correction: XI_SWITCH::Type;
 
+    }
+
+    param! {
+        /// Selects the status that is read by [Self::acquisition_status()]
+        mut acquisition_status_selector: XI_ACQUISITION_STATUS_SELECTOR::Type;
+
+        /// Status of the image acquisition selected with [Self::set_acquisition_status_selector()]
+        acquisition_status: XI_SWITCH::Type;
+
+        /// Size of the image data of a single frame in bytes
+        image_payload_size: i32;
+
         /// Select the frame counter to read
         mut counter_selector: XI_COUNTER_SELECTOR::Type;
 
@@ -667,6 +878,12 @@ impl Camera {
         /// Set a value for the feature selected with [Self::set_sensor_feature_selector]
         mut sensor_feature_value: i32;
 
+        /// Selects the temperature sensor that is read by [Self::temp()]
+        mut temp_selector: XI_TEMP_SELECTOR::Type;
+
+        /// Temperature of the selected sensor in degree Celsius
+        temp: f32;
+
         /// Read the sensor clock frequency in Hz
         sensor_clock_freq_hz: f32;
 
@@ -696,6 +913,43 @@ impl Camera {
 
         /// Configures image data delivery target to CPU RAM (default) or GPU RAM.
         mut transport_data_target: XI_TRANSPORT_DATA_TARGET_MODE::Type;
+
+        /// User set that is loaded by [Self::load_user_set()]
+        mut user_set_selector: XI_USER_SET_SELECTOR::Type;
+
+        /// User set that is loaded and made active when the camera is powered on or reset.
+        /// Note that this also affects other applications using the camera.
+        mut user_set_default: XI_USER_SET_SELECTOR::Type;
+
+        /// Activates the look-up table. See also [Self::set_lut()]
+        mut lut_en: XI_SWITCH::Type;
+
+        /// Index of the look-up table entry accessed by [Self::lut_value()]
+        mut lut_index: i32;
+
+        /// Value of the look-up table entry selected with [Self::set_lut_index()]
+        mut lut_value: i32;
+
+        /// Sharpness strength of the image processing
+        mut sharpness: f32;
+
+        /// Activates the correction of sensor defects (bad pixels).
+        mut bpc: XI_SWITCH::Type;
+
+        /// Defect list accessed by [Self::defect_list()] and [Self::set_defect_list()]
+        mut sens_defects_corr_list_selector: XI_SENS_DEFFECTS_CORR_LIST_SELECTOR::Type;
+
+        /// Status of the lens control interface. Has to be on before any other lens operation.
+        mut lens_mode: XI_SWITCH::Type;
+
+        /// Lens aperture in stops, e.g. 2.8, 4, 5.6
+        mut lens_aperture_value: f32;
+
+        /// Number of motor steps used by [Self::move_lens_focus()]
+        mut lens_focus_movement_value: i32;
+
+        /// Focal length of the lens in mm
+        lens_focal_length: f32;
     }
 }
 
@@ -751,6 +1005,67 @@ impl AcquisitionBuffer {
         self.camera.set_gain(value)
     }
 
+    runtime_param! {
+        /// Only has an effect if the acquisition timing mode is set to a frame rate mode.
+        framerate: f32;
+
+        /// Changes are applied to images that are processed after the setting is changed.
+        wb_kr: f32;
+
+        /// Changes are applied to images that are processed after the setting is changed.
+        wb_kg: f32;
+
+        /// Changes are applied to images that are processed after the setting is changed.
+        wb_kb: f32;
+
+        /// Can be used to mark images that are acquired after the setting is changed.
+        image_user_data: u32;
+
+        /// Only supported by some camera models.
+        exposure_burst_count: i32;
+
+        /// Moving the ROI during acquisition is only supported by some camera models.
+        offset_x: u32;
+
+        /// Moving the ROI during acquisition is only supported by some camera models.
+        offset_y: u32;
+
+        /// Changes are applied to images that are processed after the setting is changed.
+        sharpness: f32;
+
+        /// Use [Self::set_lut()] to change the values of the look-up table.
+        lut_en: XI_SWITCH::Type;
+
+        /// Only has an effect for hardware triggers.
+        trg_delay: i32;
+
+        /// Only supported by cameras with a lens control interface.
+        lens_aperture_value: f32;
+    }
+
+    /// Upload a look-up table during acquisition. See [Camera::set_lut()].
+    pub fn set_lut(&mut self, values: &[i32]) -> Result<(), XI_RETURN> {
+        self.camera.set_lut(values)
+    }
+
+    /// Set the signal of a general purpose output during acquisition. See [crate::Gpo::set_mode()].
+    pub fn set_gpo_mode_of(
+        &mut self,
+        port: XI_GPO_SELECTOR::Type,
+        mode: XI_GPO_MODE::Type,
+    ) -> Result<(), XI_RETURN> {
+        self.camera.gpo(port).set_mode(mode)?;
+        Ok(())
+    }
+
+    /// Calculate the white balance coefficients once from the next image.
+    ///
+    /// The coefficients are calculated from the center of the image, which should show a white or
+    /// gray target.
+    pub fn calculate_white_balance(&mut self) -> Result<(), XI_RETURN> {
+        unsafe { self.camera.set_param(XI_PRM_MANUAL_WB, 1i32) }
+    }
+
     /// Get the next image.
     ///
     /// Returns an [Image] which refers to memory in this [AcquisitionBuffer].
@@ -765,7 +1080,7 @@ impl AcquisitionBuffer {
         };
         let mut image = Image::<'a, T> {
             xi_img,
-            pix_type: PhantomData::default(),
+            pix_type: PhantomData,
         };
         let ret = unsafe {
             xiapi_sys::xiGetImage(self.camera.device_handle, timeout, &mut image.xi_img)
@@ -803,6 +1118,18 @@ impl AcquisitionBuffer {
     }
 }
 
+impl Deref for AcquisitionBuffer {
+    type Target = Camera;
+
+    /// Read-only access to the camera during acquisition.
+    ///
+    /// All getters of the camera can be used while the acquisition is running. Parameters can only
+    /// be changed with the setters of the AcquisitionBuffer.
+    fn deref(&self) -> &Self::Target {
+        &self.camera
+    }
+}
+
 unsafe impl Send for AcquisitionBuffer{
 
 }
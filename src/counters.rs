@@ -0,0 +1,179 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use std::time::Duration;
+use std::time::Instant;
+
+use xiapi_sys::*;
+
+use crate::AcquisitionBuffer;
+use crate::Camera;
+
+/// Values of all frame counters of a camera at one point in time.
+///
+/// Counters that are not supported by the camera are `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Counters {
+    /// Time at which the counters were read
+    pub captured_at: Instant,
+
+    /// Frames skipped on the transport layer, e.g. because no buffer was available
+    pub transport_skipped_frames: Option<i32>,
+
+    /// Frames skipped by the API, e.g. because they were not read in time
+    pub api_skipped_frames: Option<i32>,
+
+    /// Frames transferred to the host
+    pub transferred_frames: Option<i32>,
+
+    /// Triggers that were ignored because the previous frame was still exposed or read out
+    pub missed_triggers_overlap: Option<i32>,
+
+    /// Triggers that were ignored because the frame buffer of the camera was full
+    pub missed_triggers_buffer_overflow: Option<i32>,
+
+    /// Overflows of the frame buffer in the camera
+    pub frame_buffer_overflows: Option<i32>,
+}
+
+/// Difference between two [Counters] snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CounterDelta {
+    /// Time between the snapshots
+    pub elapsed: Duration,
+
+    /// See [Counters::transport_skipped_frames]
+    pub transport_skipped_frames: Option<i64>,
+
+    /// See [Counters::api_skipped_frames]
+    pub api_skipped_frames: Option<i64>,
+
+    /// See [Counters::transferred_frames]
+    pub transferred_frames: Option<i64>,
+
+    /// See [Counters::missed_triggers_overlap]
+    pub missed_triggers_overlap: Option<i64>,
+
+    /// See [Counters::missed_triggers_buffer_overflow]
+    pub missed_triggers_buffer_overflow: Option<i64>,
+
+    /// See [Counters::frame_buffer_overflows]
+    pub frame_buffer_overflows: Option<i64>,
+}
+
+impl Counters {
+    /// Compute the change of all counters since an earlier snapshot.
+    ///
+    /// Counters that are not available in both snapshots are `None`.
+    pub fn since(&self, earlier: &Counters) -> CounterDelta {
+        let diff = |later: Option<i32>, earlier: Option<i32>| Some(later? as i64 - earlier? as i64);
+        CounterDelta {
+            elapsed: self.captured_at.saturating_duration_since(earlier.captured_at),
+            transport_skipped_frames: diff(
+                self.transport_skipped_frames,
+                earlier.transport_skipped_frames,
+            ),
+            api_skipped_frames: diff(self.api_skipped_frames, earlier.api_skipped_frames),
+            transferred_frames: diff(self.transferred_frames, earlier.transferred_frames),
+            missed_triggers_overlap: diff(
+                self.missed_triggers_overlap,
+                earlier.missed_triggers_overlap,
+            ),
+            missed_triggers_buffer_overflow: diff(
+                self.missed_triggers_buffer_overflow,
+                earlier.missed_triggers_buffer_overflow,
+            ),
+            frame_buffer_overflows: diff(
+                self.frame_buffer_overflows,
+                earlier.frame_buffer_overflows,
+            ),
+        }
+    }
+}
+
+impl CounterDelta {
+    /// Convert a change of a counter into a rate per second.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// let cam = xiapi::open_device(None)?;
+    /// let mut acq = cam.start_acquisition()?;
+    /// let start = acq.counters()?;
+    /// std::thread::sleep(std::time::Duration::from_secs(1));
+    /// let delta = acq.counters()?.since(&start);
+    /// println!("{:?} frames per second", delta.per_second(delta.transferred_frames));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn per_second(&self, value: Option<i64>) -> Option<f64> {
+        let seconds = self.elapsed.as_secs_f64();
+        match seconds > 0.0 {
+            true => Some(value? as f64 / seconds),
+            false => None,
+        }
+    }
+
+    /// Total number of frames that were lost on the transport layer or in the API
+    pub fn lost_frames(&self) -> Option<i64> {
+        Some(self.transport_skipped_frames? + self.api_skipped_frames?)
+    }
+}
+
+impl Camera {
+    /// Read all frame counters of the camera.
+    pub fn counters(&mut self) -> Result<Counters, XI_RETURN> {
+        use XI_COUNTER_SELECTOR::*;
+        let captured_at = Instant::now();
+        Ok(Counters {
+            captured_at,
+            transport_skipped_frames: self.optional_counter(XI_CNT_SEL_TRANSPORT_SKIPPED_FRAMES)?,
+            api_skipped_frames: self.optional_counter(XI_CNT_SEL_API_SKIPPED_FRAMES)?,
+            transferred_frames: self.optional_counter(XI_CNT_SEL_TRANSPORT_TRANSFERRED_FRAMES)?,
+            missed_triggers_overlap: self
+                .optional_counter(XI_CNT_SEL_FRAME_MISSED_TRIGGER_DUETO_OVERLAP)?,
+            missed_triggers_buffer_overflow: self
+                .optional_counter(XI_CNT_SEL_FRAME_MISSED_TRIGGER_DUETO_FRAME_BUFFER_OVR)?,
+            frame_buffer_overflows: self.optional_counter(XI_CNT_SEL_FRAME_BUFFER_OVERFLOW)?,
+        })
+    }
+
+    /// Read a counter and return `None` if the camera does not support it.
+    fn optional_counter(
+        &mut self,
+        selector: XI_COUNTER_SELECTOR::Type,
+    ) -> Result<Option<i32>, XI_RETURN> {
+        match self.counter(selector) {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if is_not_supported(err) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl AcquisitionBuffer {
+    /// Read a single frame counter of the camera. See [Camera::counter].
+    pub fn counter(&mut self, counter_selector: XI_COUNTER_SELECTOR::Type) -> Result<i32, XI_RETURN> {
+        self.camera.counter(counter_selector)
+    }
+
+    /// Read all frame counters of the camera. See [Camera::counters].
+    pub fn counters(&mut self) -> Result<Counters, XI_RETURN> {
+        self.camera.counters()
+    }
+}
+
+fn is_not_supported(err: XI_RETURN) -> bool {
+    [
+        XI_RET::XI_NOT_SUPPORTED,
+        XI_RET::XI_NOT_IMPLEMENTED,
+        XI_RET::XI_NOT_SUPPORTED_PARAM,
+        XI_RET::XI_WRONG_PARAM_VALUE,
+        XI_RET::XI_PARAM_CONDITIONALLY_NOT_AVAILABLE,
+    ]
+    .iter()
+    .any(|&code| err == code as XI_RETURN)
+}
@@ -0,0 +1,343 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::ops::Deref;
+
+use xiapi_sys::*;
+
+use crate::CalibrationFrame;
+use crate::Camera;
+use crate::Image;
+use crate::OwnedImage;
+use crate::PixelValue;
+
+/// Size of the buffer used to read defect lists from the camera
+const MAX_DEFECT_LIST_SIZE: usize = 256 * 1024;
+
+/// Type of a sensor defect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DefectKind {
+    /// Pixel with a high value without light
+    Hot,
+
+    /// Pixel that does not respond to light
+    Dead,
+
+    /// Pixel with a high value that does not respond to light
+    Stuck,
+
+    /// Defect read from a list without type information, e.g. from the camera
+    Unknown,
+}
+
+/// A single defective pixel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Defect {
+    /// Horizontal position in sensor coordinates
+    pub x: u32,
+
+    /// Vertical position in sensor coordinates
+    pub y: u32,
+
+    /// Type of the defect
+    pub kind: DefectKind,
+}
+
+/// List of defective pixels.
+///
+/// Lists can be uploaded to the camera with [Camera::set_defect_list] or applied on the host
+/// with [DefectList::correct].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DefectList {
+    defects: Vec<Defect>,
+    /// Positions of the defects, to find duplicates quickly
+    positions: HashSet<(u32, u32)>,
+}
+
+impl DefectList {
+    /// Create an empty list
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a defect. A defect at a position that is already in the list is ignored.
+    pub fn push(&mut self, defect: Defect) {
+        if self.positions.insert((defect.x, defect.y)) {
+            self.defects.push(defect);
+        }
+    }
+
+    /// Check if the pixel at the given position is in the list
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        self.positions.contains(&(x, y))
+    }
+
+    /// Add all defects of another list.
+    pub fn merge(&mut self, other: &DefectList) {
+        for &defect in &other.defects {
+            self.push(defect);
+        }
+    }
+
+    /// Parse a defect list in the text format used by the camera.
+    ///
+    /// Every line contains the horizontal and vertical position of one pixel, separated by
+    /// whitespace, a comma or a semicolon. Empty lines and lines starting with `#` are ignored.
+    /// Returns `XI_INVALID_DATA` if a line can not be parsed.
+    pub fn parse(content: &str) -> Result<Self, XI_RETURN> {
+        let mut list = DefectList::new();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut values = line
+                .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+                .filter(|value| !value.is_empty())
+                .map(str::parse::<u32>);
+            match (values.next(), values.next(), values.next()) {
+                (Some(Ok(x)), Some(Ok(y)), None) => list.push(Defect {
+                    x,
+                    y,
+                    kind: DefectKind::Unknown,
+                }),
+                _ => return Err(XI_RET::XI_INVALID_DATA as XI_RETURN),
+            }
+        }
+        Ok(list)
+    }
+
+    /// Format the list in the text format used by the camera.
+    ///
+    /// See [Self::parse()] for a description of the format.
+    pub fn to_list_content(&self) -> String {
+        let mut content = String::new();
+        for defect in &self.defects {
+            let _ = writeln!(content, "{} {}", defect.x, defect.y);
+        }
+        content
+    }
+
+    /// Replace all defective pixels of an image by the average of their nearest good neighbors.
+    ///
+    /// Positions in the list are sensor coordinates, so the ROI offset of the image is taken into
+    /// account. For RAW images of color sensors, only neighbors with the same color filter are
+    /// used. Planar images are converted to the interleaved format first.
+    pub fn correct<T: PixelValue>(&self, image: &Image<T>) -> OwnedImage<T> {
+        if image.is_planar() {
+            return self.correct(&image.to_interleaved().image());
+        }
+        let mut corrected = image.to_owned_image();
+        let nb_channels = image.nb_channels();
+        let distance = match image.color_filter_array() {
+            XI_COLOR_FILTER_ARRAY::XI_CFA_NONE => 1,
+            _ if nb_channels == 1 => 2,
+            _ => 1,
+        };
+        let (width, height) = (image.width() as i64, image.height() as i64);
+        let offset_x = image.absolute_offset_x() as i64;
+        let offset_y = image.absolute_offset_y() as i64;
+        let defective: HashSet<(i64, i64)> = self
+            .defects
+            .iter()
+            .map(|defect| (defect.x as i64 - offset_x, defect.y as i64 - offset_y))
+            .collect();
+
+        for &(x, y) in &defective {
+            if !(0..width).contains(&x) || !(0..height).contains(&y) {
+                continue;
+            }
+            let neighbors: Vec<(i64, i64)> = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .iter()
+                .map(|(dx, dy)| (x + dx * distance, y + dy * distance))
+                .filter(|&(nx, ny)| (0..width).contains(&nx) && (0..height).contains(&ny))
+                .filter(|neighbor| !defective.contains(neighbor))
+                .collect();
+            if neighbors.is_empty() {
+                continue;
+            }
+            for channel in 0..nb_channels {
+                let sum: f32 = neighbors
+                    .iter()
+                    .filter_map(|&(nx, ny)| {
+                        let row = image.row(ny as usize)?;
+                        Some(row[nx as usize * nb_channels + channel].to_f32())
+                    })
+                    .sum();
+                if let Some(row) = corrected.row_mut(y as usize) {
+                    row[x as usize * nb_channels + channel] =
+                        T::from_f32(sum / neighbors.len() as f32);
+                }
+            }
+        }
+        corrected
+    }
+}
+
+impl Deref for DefectList {
+    type Target = [Defect];
+
+    fn deref(&self) -> &Self::Target {
+        &self.defects
+    }
+}
+
+impl FromIterator<Defect> for DefectList {
+    fn from_iter<I: IntoIterator<Item = Defect>>(iter: I) -> Self {
+        let mut list = DefectList::new();
+        for defect in iter {
+            list.push(defect);
+        }
+        list
+    }
+}
+
+/// Finds defective pixels in dark and flat calibration frames.
+///
+/// Frames can be captured with [AcquisitionBuffer::capture_average]. The dark frame should be
+/// captured with the longest exposure time that is used in the application, as hot pixels become
+/// more visible with longer exposures.
+///
+/// [AcquisitionBuffer::capture_average]: crate::AcquisitionBuffer::capture_average
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DefectDetector {
+    /// A pixel is hot if its dark value exceeds the mean of the pixels of the same color in the
+    /// dark frame by more than this fraction of the saturation level.
+    pub hot_threshold: f32,
+
+    /// A pixel does not respond to light if its response (flat minus dark) is less than this
+    /// fraction of the mean response of the pixels of the same color.
+    pub min_response: f32,
+
+    /// Horizontal offset of the frames on the sensor
+    pub offset_x: u32,
+
+    /// Vertical offset of the frames on the sensor
+    pub offset_y: u32,
+}
+
+impl Default for DefectDetector {
+    fn default() -> Self {
+        DefectDetector {
+            hot_threshold: 0.05,
+            min_response: 0.5,
+            offset_x: 0,
+            offset_y: 0,
+        }
+    }
+}
+
+impl DefectDetector {
+    /// Find defective pixels in a dark and a flat frame.
+    ///
+    /// * Pixels that are bright in the dark frame are reported as [DefectKind::Hot].
+    /// * Pixels that do not respond to light are reported as [DefectKind::Dead].
+    /// * Pixels that are bright in the dark frame and do not respond to light are reported as
+    ///   [DefectKind::Stuck].
+    ///
+    /// Every channel, and every position of the 2x2 pattern of RAW frames with a Bayer filter,
+    /// is compared with its own mean value, as the response differs between colors. For frames
+    /// with several channels, a pixel is defective if any channel is defective.
+    /// Returns `XI_INVALID_ARG` if the frames do not have the same size and format.
+    pub fn detect(
+        &self,
+        dark: &CalibrationFrame,
+        flat: &CalibrationFrame,
+    ) -> Result<DefectList, XI_RETURN> {
+        if dark.width() != flat.width()
+            || dark.height() != flat.height()
+            || dark.nb_channels() != flat.nb_channels()
+            || dark.is_bayer() != flat.is_bayer()
+        {
+            return Err(XI_RET::XI_INVALID_ARG as XI_RETURN);
+        }
+        let dark_means = dark.plane_means();
+        let flat_means = flat.plane_means();
+        let hot_levels: Vec<f32> = dark_means
+            .iter()
+            .map(|mean| mean + self.hot_threshold * dark.saturation())
+            .collect();
+        let min_responses: Vec<f32> = flat_means
+            .iter()
+            .zip(&dark_means)
+            .map(|(flat_mean, dark_mean)| self.min_response * (flat_mean - dark_mean))
+            .collect();
+        let nb_channels = dark.nb_channels().max(1);
+
+        let mut list = DefectList::new();
+        let mut last_pixel = None;
+        let values = dark.data().iter().zip(flat.data());
+        for (i, (&dark_value, &flat_value)) in values.enumerate() {
+            let plane = dark.plane(i);
+            let hot = dark_value > hot_levels[plane];
+            let insensitive = flat_value - dark_value < min_responses[plane];
+            let kind = match (hot, insensitive) {
+                (true, true) => DefectKind::Stuck,
+                (true, false) => DefectKind::Hot,
+                (false, true) => DefectKind::Dead,
+                (false, false) => continue,
+            };
+            let pixel = (i / nb_channels) as u32;
+            // Channels of the same pixel are next to each other
+            if last_pixel == Some(pixel) {
+                continue;
+            }
+            last_pixel = Some(pixel);
+            list.push(Defect {
+                x: pixel % dark.width() + self.offset_x,
+                y: pixel / dark.width() + self.offset_y,
+                kind,
+            });
+        }
+        Ok(list)
+    }
+}
+
+impl Camera {
+    /// Read a sensor defect list from the camera.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// use xiapi::XI_SENS_DEFFECTS_CORR_LIST_SELECTOR::XI_SENS_DEFFECTS_CORR_LIST_SEL_FACTORY;
+    /// let mut cam = xiapi::open_device(None)?;
+    /// let factory_list = cam.defect_list(XI_SENS_DEFFECTS_CORR_LIST_SEL_FACTORY)?;
+    /// println!("{} defective pixels", factory_list.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn defect_list(
+        &mut self,
+        selector: XI_SENS_DEFFECTS_CORR_LIST_SELECTOR::Type,
+    ) -> Result<DefectList, XI_RETURN> {
+        let mut buffer = vec![0u8; MAX_DEFECT_LIST_SIZE];
+        let length = self.with_selector(XI_PRM_SENS_DEFECTS_CORR_LIST_SELECTOR, selector, |cam| unsafe {
+            cam.param_bytes(XI_PRM_SENS_DEFECTS_CORR_LIST_CONTENT, &mut buffer)
+        })?;
+        buffer.truncate(length);
+        if let Some(end) = buffer.iter().position(|&c| c == 0) {
+            buffer.truncate(end);
+        }
+        let content = String::from_utf8(buffer).or(Err(XI_RET::XI_INVALID_DATA as XI_RETURN))?;
+        DefectList::parse(&content)
+    }
+
+    /// Write a sensor defect list to the camera.
+    ///
+    /// The factory list can not be overwritten, use one of the user lists instead. The list is
+    /// used when the defect correction is enabled with [Self::set_bpc()].
+    pub fn set_defect_list(
+        &mut self,
+        selector: XI_SENS_DEFFECTS_CORR_LIST_SELECTOR::Type,
+        list: &DefectList,
+    ) -> Result<(), XI_RETURN> {
+        let content = list.to_list_content();
+        self.with_selector(XI_PRM_SENS_DEFECTS_CORR_LIST_SELECTOR, selector, |cam| unsafe {
+            cam.set_param_string(XI_PRM_SENS_DEFECTS_CORR_LIST_CONTENT, &content)
+        })
+    }
+}
@@ -0,0 +1,243 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Write;
+use std::mem::size_of;
+use std::path::Path;
+
+use xiapi_sys::*;
+
+use crate::tiff::*;
+use crate::Camera;
+use crate::Image;
+use crate::PixelValue;
+
+// DNG tags
+const NEW_SUBFILE_TYPE: u16 = 254;
+const ORIENTATION: u16 = 274;
+const DNG_VERSION: u16 = 50706;
+const DNG_BACKWARD_VERSION: u16 = 50707;
+const UNIQUE_CAMERA_MODEL: u16 = 50708;
+const CFA_PLANE_COLOR: u16 = 50710;
+const CFA_LAYOUT: u16 = 50711;
+const BLACK_LEVEL: u16 = 50714;
+const WHITE_LEVEL: u16 = 50717;
+const COLOR_MATRIX_1: u16 = 50721;
+const AS_SHOT_NEUTRAL: u16 = 50728;
+const CAMERA_SERIAL_NUMBER: u16 = 50735;
+const CALIBRATION_ILLUMINANT_1: u16 = 50778;
+
+/// EXIF light source D65
+const D65: u16 = 21;
+
+/// Conversion from CIE XYZ (D65) to linear sRGB
+const XYZ_TO_SRGB: [[f32; 3]; 3] = [
+    [3.240454, -1.537139, -0.498531],
+    [-0.969266, 1.876011, 0.041556],
+    [0.055643, -0.204026, 1.057225],
+];
+
+/// Parameters of the color correction matrix, row by row
+const CC_MATRIX: [[&[u8]; 3]; 3] = [
+    [XI_PRM_CC_MATRIX_00, XI_PRM_CC_MATRIX_01, XI_PRM_CC_MATRIX_02],
+    [XI_PRM_CC_MATRIX_10, XI_PRM_CC_MATRIX_11, XI_PRM_CC_MATRIX_12],
+    [XI_PRM_CC_MATRIX_20, XI_PRM_CC_MATRIX_21, XI_PRM_CC_MATRIX_22],
+];
+
+/// Camera information that is stored in DNG files.
+///
+/// Used by [Image::write_dng()].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DngInfo {
+    /// Model name of the camera
+    pub model: Option<String>,
+
+    /// Serial number of the camera
+    pub serial_number: Option<String>,
+
+    /// Number of bits of the pixel values, used to compute the white level. The full range of the
+    /// pixel type is used if this is `None`.
+    pub bit_depth: Option<u16>,
+
+    /// White balance coefficients for red, green and blue. If this is `None`, the coefficients
+    /// in the image are used.
+    pub white_balance: Option<[f32; 3]>,
+
+    /// Matrix converting white balanced camera RGB to linear sRGB, see
+    /// [Camera::color_correction_matrix()]. sRGB primaries are assumed if this is `None`.
+    pub color_matrix: Option<[[f32; 3]; 3]>,
+}
+
+impl DngInfo {
+    /// Read the model, serial number, bit depth, white balance and color correction matrix of a
+    /// camera.
+    ///
+    /// The color correction matrix is left empty if the camera does not provide it.
+    pub fn for_camera(camera: &Camera) -> Result<Self, XI_RETURN> {
+        Ok(DngInfo {
+            model: Some(unsafe { camera.param_string(XI_PRM_DEVICE_NAME) }?),
+            serial_number: Some(camera.serial_number()?),
+            bit_depth: Some(camera.image_data_bit_depth()? as u16),
+            white_balance: Some([camera.wb_kr()?, camera.wb_kg()?, camera.wb_kb()?]),
+            color_matrix: camera.color_correction_matrix().ok(),
+        })
+    }
+}
+
+impl Camera {
+    /// Read the color correction matrix that converts white balanced camera RGB to sRGB.
+    pub fn color_correction_matrix(&self) -> Result<[[f32; 3]; 3], XI_RETURN> {
+        let mut matrix = [[0.0; 3]; 3];
+        for (row, params) in matrix.iter_mut().zip(CC_MATRIX) {
+            for (value, param) in row.iter_mut().zip(params) {
+                *value = unsafe { self.param(param) }?;
+            }
+        }
+        Ok(matrix)
+    }
+}
+
+impl<T: PixelValue> Image<'_, T> {
+    /// Write a RAW Bayer image as DNG file.
+    ///
+    /// Only `XI_RAW8` and `XI_RAW16` images with a Bayer color filter array are supported. The
+    /// black level is taken from the image, the white level from the bit depth.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use xiapi::XI_IMG_FORMAT::XI_RAW16;
+    /// let mut cam = xiapi::open_device(None).map_err(|err| format!("xiAPI error {err}"))?;
+    /// cam.set_image_data_format(XI_RAW16).map_err(|err| format!("xiAPI error {err}"))?;
+    /// let info = xiapi::DngInfo::for_camera(&cam).map_err(|err| format!("xiAPI error {err}"))?;
+    /// let acq = cam.start_acquisition().map_err(|err| format!("xiAPI error {err}"))?;
+    /// let image = acq.next_image::<u16>(None).map_err(|err| format!("xiAPI error {err}"))?;
+    /// image.save_dng("raw.dng", &info)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_dng<W: Write>(&self, writer: &mut W, info: &DngInfo) -> io::Result<()> {
+        let sample_size = match self.format() {
+            XI_IMG_FORMAT::XI_RAW8 => 1,
+            XI_IMG_FORMAT::XI_RAW16 => 2,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "DNG requires RAW8 or RAW16 images")),
+        };
+        if sample_size != size_of::<T>() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "pixel type does not match the image format"));
+        }
+        let pattern = cfa_pattern(self.color_filter_array())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "image has no Bayer color filter array"))?;
+        let bits = (sample_size * 8) as u16;
+        let bit_depth = info.bit_depth.filter(|&b| b > 0 && b <= bits).unwrap_or(bits);
+        let model = info.model.as_deref().unwrap_or("XIMEA camera");
+        let mut entries = vec![
+            IfdEntry::long(NEW_SUBFILE_TYPE, &[0]),
+            IfdEntry::long(IMAGE_WIDTH, &[self.width()]),
+            IfdEntry::long(IMAGE_LENGTH, &[self.height()]),
+            IfdEntry::short(BITS_PER_SAMPLE, &[bits]),
+            IfdEntry::short(COMPRESSION, &[1]),
+            IfdEntry::short(PHOTOMETRIC_INTERPRETATION, &[CFA]),
+            IfdEntry::ascii(MAKE, "XIMEA"),
+            IfdEntry::ascii(MODEL, model),
+            IfdEntry::short(ORIENTATION, &[1]),
+            IfdEntry::short(SAMPLES_PER_PIXEL, &[1]),
+            IfdEntry::long(ROWS_PER_STRIP, &[self.height()]),
+            IfdEntry::short(PLANAR_CONFIGURATION, &[1]),
+            IfdEntry::ascii(SOFTWARE, concat!("xiapi ", env!("CARGO_PKG_VERSION"))),
+            IfdEntry::short(CFA_REPEAT_PATTERN_DIM, &[2, 2]),
+            IfdEntry::byte(CFA_PATTERN, &pattern),
+            IfdEntry::rational(EXPOSURE_TIME, &[(self.exposure_time_us(), 1_000_000)]),
+            IfdEntry::long(IMAGE_NUMBER, &[self.nframe()]),
+            IfdEntry::byte(DNG_VERSION, &[1, 4, 0, 0]),
+            IfdEntry::byte(DNG_BACKWARD_VERSION, &[1, 1, 0, 0]),
+            IfdEntry::ascii(UNIQUE_CAMERA_MODEL, &format!("XIMEA {}", model)),
+            IfdEntry::byte(CFA_PLANE_COLOR, &[0, 1, 2]),
+            IfdEntry::short(CFA_LAYOUT, &[1]),
+            IfdEntry::long(BLACK_LEVEL, &[self.black_level()]),
+            IfdEntry::long(WHITE_LEVEL, &[(1u32 << bit_depth) - 1]),
+            IfdEntry::short(CALIBRATION_ILLUMINANT_1, &[D65]),
+        ];
+        let image_white_balance = [self.xi_img.wb_red, self.xi_img.wb_green, self.xi_img.wb_blue];
+        let white_balance = info
+            .white_balance
+            .or(Some(image_white_balance))
+            .filter(|coefficients| coefficients.iter().all(|&k| k > 0.0));
+        let color_matrix = dng_color_matrix(info.color_matrix, white_balance);
+        entries.push(IfdEntry::srational(COLOR_MATRIX_1, &to_srational(&color_matrix)));
+        if let Some(coefficients) = white_balance {
+            // The neutral color in camera space is the inverse of the white balance gains
+            let neutral: Vec<_> = coefficients
+                .iter()
+                .map(|&k| ((1_000_000.0 / k).round() as u32, 1_000_000))
+                .collect();
+            entries.push(IfdEntry::rational(AS_SHOT_NEUTRAL, &neutral));
+        }
+        if let Some(serial_number) = &info.serial_number {
+            entries.push(IfdEntry::ascii(CAMERA_SERIAL_NUMBER, serial_number));
+        }
+        write_tiff_file(writer, &self.packed_samples(1, &[0]), entries)
+    }
+
+    /// Write a RAW Bayer image to a DNG file. See [Self::write_dng()].
+    pub fn save_dng<P: AsRef<Path>>(&self, path: P, info: &DngInfo) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_dng(&mut writer, info)?;
+        writer.flush()
+    }
+}
+
+/// Compute the DNG color matrix, which converts XYZ to camera RGB before white balance.
+///
+/// `camera_to_srgb` converts white balanced camera RGB to linear sRGB. If it is not known, the
+/// camera is assumed to have sRGB primaries.
+pub(crate) fn dng_color_matrix(
+    camera_to_srgb: Option<[[f32; 3]; 3]>,
+    white_balance: Option<[f32; 3]>,
+) -> [[f32; 3]; 3] {
+    let xyz_to_camera = match camera_to_srgb.and_then(invert) {
+        Some(srgb_to_camera) => multiply(&srgb_to_camera, &XYZ_TO_SRGB),
+        None => XYZ_TO_SRGB,
+    };
+    let [kr, kg, kb] = white_balance.unwrap_or([1.0; 3]);
+    let inverse_white_balance = [[1.0 / kr, 0.0, 0.0], [0.0, 1.0 / kg, 0.0], [0.0, 0.0, 1.0 / kb]];
+    multiply(&inverse_white_balance, &xyz_to_camera)
+}
+
+fn to_srational(matrix: &[[f32; 3]; 3]) -> Vec<(i32, i32)> {
+    matrix
+        .iter()
+        .flatten()
+        .map(|&value| ((value * 10_000.0).round() as i32, 10_000))
+        .collect()
+}
+
+fn multiply(a: &[[f32; 3]; 3], b: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let mut result = [[0.0; 3]; 3];
+    for (i, row) in result.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    result
+}
+
+fn invert(m: [[f32; 3]; 3]) -> Option<[[f32; 3]; 3]> {
+    let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    let determinant =
+        m[0][0] * cofactor(1, 2, 1, 2) - m[0][1] * cofactor(1, 2, 0, 2) + m[0][2] * cofactor(1, 2, 0, 1);
+    if determinant.abs() < 1e-6 {
+        return None;
+    }
+    let adjugate = [
+        [cofactor(1, 2, 1, 2), -cofactor(0, 2, 1, 2), cofactor(0, 1, 1, 2)],
+        [-cofactor(1, 2, 0, 2), cofactor(0, 2, 0, 2), -cofactor(0, 1, 0, 2)],
+        [cofactor(1, 2, 0, 1), -cofactor(0, 2, 0, 1), cofactor(0, 1, 0, 1)],
+    ];
+    Some(adjugate.map(|row| row.map(|value| value / determinant)))
+}
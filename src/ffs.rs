@@ -0,0 +1,213 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use std::io;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Write;
+
+use xiapi_sys::*;
+
+use crate::Camera;
+
+/// Upper bound for the number of files that are enumerated by [Camera::ffs_files]
+const MAX_FFS_FILES: i32 = 1024;
+
+/// A file in the flash file system of the camera
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FfsFile {
+    /// File number
+    pub id: i32,
+
+    /// Name of the file
+    pub name: String,
+
+    /// Size of the file in bytes
+    pub size: usize,
+}
+
+impl Camera {
+    /// List all files in the flash file system (FFS) of the camera.
+    ///
+    /// The list ends at the first file number that is out of range or has no name. Other errors
+    /// are returned.
+    pub fn ffs_files(&mut self) -> Result<Vec<FfsFile>, XI_RETURN> {
+        let out_of_range = XI_RET::XI_OUT_OF_RANGE as XI_RETURN;
+        let mut files = Vec::new();
+        for id in 0..MAX_FFS_FILES {
+            match unsafe { self.set_param(XI_PRM_FFS_FILE_ID, id) } {
+                Err(err) if err == out_of_range => break,
+                result => result?,
+            }
+            let name = match unsafe { self.param_string(XI_PRM_FFS_FILE_NAME) } {
+                Ok(name) if name.is_empty() => break,
+                Err(err) if err == out_of_range => break,
+                result => result?,
+            };
+            let size = unsafe { self.param::<i32>(XI_PRM_FFS_FILE_SIZE) }?;
+            files.push(FfsFile {
+                id,
+                name,
+                size: size.max(0) as usize,
+            });
+        }
+        Ok(files)
+    }
+
+    /// Read a complete file from the flash file system of the camera.
+    pub fn ffs_read(&mut self, name: &str) -> Result<Vec<u8>, XI_RETURN> {
+        unsafe {
+            self.set_param_string(XI_PRM_FFS_FILE_NAME, name)?;
+            let size = self.param::<i32>(XI_PRM_FFS_FILE_SIZE)?.max(0) as usize;
+            let mut data = vec![0u8; size];
+            let length = self.param_bytes(XI_PRM_READ_FILE_FFS, &mut data)?;
+            data.truncate(length);
+            Ok(data)
+        }
+    }
+
+    /// Write a complete file to the flash file system of the camera.
+    ///
+    /// An existing file with the same name is replaced. Returns `XI_BUFFER_TOO_SMALL` without
+    /// writing anything if the free space of the FFS is not sufficient.
+    ///
+    /// Some cameras require an access key to be set with [Self::set_ffs_access_key()] before
+    /// files can be written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// let mut cam = xiapi::open_device(None)?;
+    /// cam.ffs_write("calibration.bin", &[1, 2, 3, 4])?;
+    /// assert_eq!(cam.ffs_read("calibration.bin")?, vec![1, 2, 3, 4]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn ffs_write(&mut self, name: &str, data: &[u8]) -> Result<(), XI_RETURN> {
+        // Space of an existing file with the same name is reused
+        let existing_size = self
+            .ffs_files()?
+            .into_iter()
+            .find(|file| file.name == name)
+            .map_or(0, |file| file.size);
+        if data.len() > self.ffs_free_size()? + existing_size {
+            return Err(XI_RET::XI_BUFFER_TOO_SMALL as XI_RETURN);
+        }
+        unsafe {
+            self.set_param_string(XI_PRM_FFS_FILE_NAME, name)?;
+            self.set_param_bytes(XI_PRM_WRITE_FILE_FFS, data)
+        }
+    }
+
+    /// Truncate a file in the flash file system of the camera to zero length.
+    ///
+    /// xiAPI has no operation to delete files, so the file is replaced by an empty file and is
+    /// still listed by [Self::ffs_files()] with a size of 0.
+    pub fn ffs_truncate(&mut self, name: &str) -> Result<(), XI_RETURN> {
+        unsafe {
+            self.set_param_string(XI_PRM_FFS_FILE_NAME, name)?;
+            self.set_param_bytes(XI_PRM_WRITE_FILE_FFS, &[])
+        }
+    }
+
+    /// Open a file in the flash file system of the camera for reading.
+    ///
+    /// The whole file is read when this function is called.
+    pub fn ffs_reader(&mut self, name: &str) -> Result<FfsReader, XI_RETURN> {
+        Ok(FfsReader {
+            data: Cursor::new(self.ffs_read(name)?),
+        })
+    }
+
+    /// Create a file in the flash file system of the camera for writing.
+    ///
+    /// The data is collected in memory and written to the camera when the writer is flushed or
+    /// dropped. Nothing is written if no data was written to the writer, so an existing file is
+    /// kept. [FfsWriter::finish] is the only way to handle errors that occur while writing.
+    pub fn ffs_writer(&mut self, name: &str) -> FfsWriter<'_> {
+        FfsWriter {
+            camera: self,
+            name: name.to_string(),
+            data: Vec::new(),
+            dirty: false,
+        }
+    }
+
+    /// Free space in the flash file system of the camera in bytes.
+    pub fn ffs_free_size(&self) -> Result<usize, XI_RETURN> {
+        let size = unsafe { self.param::<i32>(XI_PRM_FREE_FFS_SIZE) }?;
+        Ok(size.max(0) as usize)
+    }
+
+    /// Used space in the flash file system of the camera in bytes.
+    pub fn ffs_used_size(&self) -> Result<usize, XI_RETURN> {
+        let size = unsafe { self.param::<i32>(XI_PRM_USED_FFS_SIZE) }?;
+        Ok(size.max(0) as usize)
+    }
+
+    /// Set the key that enables file operations on some cameras.
+    pub fn set_ffs_access_key(&mut self, key: i32) -> Result<(), XI_RETURN> {
+        unsafe { self.set_param(XI_PRM_FFS_ACCESS_KEY, key) }
+    }
+}
+
+/// Reader for a file from the flash file system of the camera.
+///
+/// Created by [Camera::ffs_reader].
+pub struct FfsReader {
+    data: Cursor<Vec<u8>>,
+}
+
+impl Read for FfsReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.data.read(buf)
+    }
+}
+
+/// Writer for a file in the flash file system of the camera.
+///
+/// Created by [Camera::ffs_writer]. Data that was not written yet is written when the writer is
+/// dropped, but errors, e.g. when the FFS is full, can not be reported then. Call
+/// [FfsWriter::finish] to check that the file was written.
+pub struct FfsWriter<'a> {
+    camera: &'a mut Camera,
+    name: String,
+    data: Vec<u8>,
+    dirty: bool,
+}
+
+impl FfsWriter<'_> {
+    /// Write the file to the camera and return any error that occurs.
+    pub fn finish(mut self) -> Result<(), XI_RETURN> {
+        self.dirty = false;
+        self.camera.ffs_write(&self.name, &self.data)
+    }
+}
+
+impl Write for FfsWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.dirty = true;
+        self.data.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.dirty {
+            self.camera
+                .ffs_write(&self.name, &self.data)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, format!("xiAPI error {err}")))?;
+            self.dirty = false;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FfsWriter<'_> {
+    fn drop(&mut self) {
+        if let Err(_err) = self.flush() {
+            debug_assert!(std::thread::panicking(), "Could not write FFS file {}, Error:{_err}", self.name);
+        }
+    }
+}
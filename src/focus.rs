@@ -0,0 +1,240 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use xiapi_sys::*;
+
+use crate::tiff::cfa_pattern;
+use crate::AcquisitionBuffer;
+use crate::Camera;
+use crate::Image;
+use crate::PixelValue;
+use crate::Roi;
+
+/// Measure of image sharpness, which is largest when the image is in focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusMetric {
+    /// Variance of the Laplacian of the image
+    LaplacianVariance,
+
+    /// Mean squared gradient magnitude of the Sobel operator
+    Tenengrad,
+
+    /// Mean squared difference of pixels two columns apart
+    Brenner,
+}
+
+/// Options for [AcquisitionBuffer::focus_sweep()].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FocusSweepConfig {
+    /// Number of focus moves. The focus is measured at the start and after every move.
+    pub steps: u32,
+
+    /// Motor steps per move. Negative values move in the opposite direction.
+    pub step_size: i32,
+
+    /// Number of frames that are skipped after every move, because they may have been exposed
+    /// while the lens was moving.
+    pub settle_frames: u32,
+
+    /// Metric used to score the frames
+    pub metric: FocusMetric,
+
+    /// Region of the image that is scored. The whole image is used if this is `None`.
+    pub roi: Option<Roi>,
+
+    /// Timeout for every frame in milliseconds
+    pub timeout: Option<u32>,
+}
+
+impl Default for FocusSweepConfig {
+    fn default() -> Self {
+        FocusSweepConfig {
+            steps: 20,
+            step_size: 10,
+            settle_frames: 2,
+            metric: FocusMetric::LaplacianVariance,
+            roi: None,
+            timeout: Some(1000),
+        }
+    }
+}
+
+/// Result of a focus sweep.
+///
+/// Positions are in motor steps relative to the focus at the start of the sweep.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FocusSweep {
+    /// Focus score at each measured position
+    pub scores: Vec<(i32, f32)>,
+
+    /// Position with the highest score, where the lens is left after the sweep
+    pub best_position: i32,
+}
+
+impl Camera {
+    /// Move the focus motor of the lens by the given number of steps.
+    ///
+    /// [Self::lens_mode()] has to be on.
+    pub fn move_lens_focus(&mut self, steps: i32) -> Result<(), XI_RETURN> {
+        self.set_lens_focus_movement_value(steps)?;
+        unsafe { self.set_param(XI_PRM_LENS_FOCUS_MOVE, 0) }
+    }
+}
+
+impl AcquisitionBuffer {
+    /// Move the focus motor of the lens during acquisition. See [Camera::move_lens_focus()].
+    pub fn move_lens_focus(&mut self, steps: i32) -> Result<(), XI_RETURN> {
+        self.camera.move_lens_focus(steps)
+    }
+
+    /// Step the focus of the lens through a range, score a frame at every position and move the
+    /// lens to the sharpest position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// let mut cam = xiapi::open_device(None)?;
+    /// cam.set_lens_mode(xiapi::XI_SWITCH::XI_ON)?;
+    /// let mut acq = cam.start_acquisition()?;
+    /// let config = xiapi::FocusSweepConfig {
+    ///     steps: 50,
+    ///     step_size: 20,
+    ///     ..Default::default()
+    /// };
+    /// let sweep = acq.focus_sweep::<u8>(&config)?;
+    /// println!("Best focus {} steps from the start", sweep.best_position);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn focus_sweep<T: PixelValue>(&mut self, config: &FocusSweepConfig) -> Result<FocusSweep, XI_RETURN> {
+        let mut scores = Vec::with_capacity(config.steps as usize + 1);
+        let mut position = 0;
+        for step in 0..=config.steps {
+            if step > 0 {
+                self.move_lens_focus(config.step_size)?;
+                position += config.step_size;
+                for _ in 0..config.settle_frames {
+                    self.next_image::<T>(config.timeout)?;
+                }
+            }
+            let image = self.next_image::<T>(config.timeout)?;
+            scores.push((position, image.focus_score(config.metric, config.roi.as_ref())?));
+        }
+        let best_position = scores
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0, |&(position, _)| position);
+        if best_position != position {
+            self.move_lens_focus(best_position - position)?;
+        }
+        Ok(FocusSweep {
+            scores,
+            best_position,
+        })
+    }
+}
+
+impl<T: PixelValue> Image<'_, T> {
+    /// Compute a focus score of the image or a region of it.
+    ///
+    /// Color images are converted to gray values first. RAW images with a Bayer filter are
+    /// scored on neighboring pixels of the same color.
+    ///
+    /// Returns `XI_OUT_OF_RANGE` if the ROI is not inside the image and
+    /// `XI_NOT_SUPPORTED_DATA_FORMAT` for packed transport data.
+    pub fn focus_score(&self, metric: FocusMetric, roi: Option<&Roi>) -> Result<f32, XI_RETURN> {
+        let nb_channels = self.nb_channels();
+        if nb_channels == 0 || self.format() == XI_IMG_FORMAT::XI_FRM_TRANSPORT_DATA {
+            return Err(XI_RET::XI_NOT_SUPPORTED_DATA_FORMAT as XI_RETURN);
+        }
+        let roi = roi.copied().unwrap_or(Roi {
+            offset_x: 0,
+            offset_y: 0,
+            width: self.width(),
+            height: self.height(),
+        });
+        if roi.offset_x as u64 + roi.width as u64 > self.width() as u64
+            || roi.offset_y as u64 + roi.height as u64 > self.height() as u64
+        {
+            return Err(XI_RET::XI_OUT_OF_RANGE as XI_RETURN);
+        }
+        let (width, height) = (roi.width as usize, roi.height as usize);
+        // Gray values of the ROI; an alpha channel is ignored
+        let colors = nb_channels.min(3);
+        let mut gray = Vec::with_capacity(width * height);
+        for y in roi.offset_y as usize..roi.offset_y as usize + height {
+            for x in roi.offset_x as usize..roi.offset_x as usize + width {
+                let sum: f32 = (0..colors)
+                    .filter_map(|channel| self.sample(x, y, channel))
+                    .map(|value| value.to_f32())
+                    .sum();
+                gray.push(sum / colors as f32);
+            }
+        }
+        let bayer = cfa_pattern(self.color_filter_array()).is_some()
+            && matches!(self.format(), XI_IMG_FORMAT::XI_RAW8 | XI_IMG_FORMAT::XI_RAW16);
+        let distance = if bayer { 2 } else { 1 };
+        Ok(focus_score(&gray, width, height, distance, metric))
+    }
+}
+
+/// Compute a focus metric of gray values, comparing pixels `d` pixels apart.
+fn focus_score(gray: &[f32], width: usize, height: usize, d: usize, metric: FocusMetric) -> f32 {
+    let at = |x: usize, y: usize| gray[y * width + x];
+    let mut sum = 0.0f64;
+    let mut sum_squares = 0.0f64;
+    let mut count = 0usize;
+    match metric {
+        FocusMetric::LaplacianVariance | FocusMetric::Tenengrad => {
+            if width <= 2 * d || height <= 2 * d {
+                return 0.0;
+            }
+            for y in d..height - d {
+                for x in d..width - d {
+                    let value = match metric {
+                        FocusMetric::LaplacianVariance => {
+                            4.0 * at(x, y) - at(x - d, y) - at(x + d, y) - at(x, y - d) - at(x, y + d)
+                        }
+                        _ => {
+                            let gx = at(x + d, y - d) + 2.0 * at(x + d, y) + at(x + d, y + d)
+                                - at(x - d, y - d)
+                                - 2.0 * at(x - d, y)
+                                - at(x - d, y + d);
+                            let gy = at(x - d, y + d) + 2.0 * at(x, y + d) + at(x + d, y + d)
+                                - at(x - d, y - d)
+                                - 2.0 * at(x, y - d)
+                                - at(x + d, y - d);
+                            gx * gx + gy * gy
+                        }
+                    };
+                    sum += value as f64;
+                    sum_squares += (value * value) as f64;
+                    count += 1;
+                }
+            }
+        }
+        FocusMetric::Brenner => {
+            if width <= 2 * d {
+                return 0.0;
+            }
+            for y in 0..height {
+                for x in 0..width - 2 * d {
+                    let difference = at(x + 2 * d, y) - at(x, y);
+                    sum += (difference * difference) as f64;
+                    count += 1;
+                }
+            }
+        }
+    }
+    if count == 0 {
+        return 0.0;
+    }
+    let mean = sum / count as f64;
+    match metric {
+        FocusMetric::LaplacianVariance => (sum_squares / count as f64 - mean * mean).max(0.0) as f32,
+        _ => mean as f32,
+    }
+}
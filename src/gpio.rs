@@ -0,0 +1,527 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use std::thread::sleep;
+use std::time::Duration;
+use std::time::Instant;
+
+use xiapi_sys::*;
+
+use crate::AcquisitionBuffer;
+use crate::Camera;
+
+/// Polarity of a GPO signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpoPolarity {
+    /// The output is high while the signal is active
+    ActiveHigh,
+
+    /// The output is low while the signal is active
+    ActiveLow,
+}
+
+/// Pairs of GPO modes that output the same signal with opposite polarity
+const GPO_MODE_PAIRS: [(XI_GPO_MODE::Type, XI_GPO_MODE::Type); 8] = [
+    (XI_GPO_MODE::XI_GPO_ON, XI_GPO_MODE::XI_GPO_OFF),
+    (XI_GPO_MODE::XI_GPO_FRAME_ACTIVE, XI_GPO_MODE::XI_GPO_FRAME_ACTIVE_NEG),
+    (XI_GPO_MODE::XI_GPO_EXPOSURE_ACTIVE, XI_GPO_MODE::XI_GPO_EXPOSURE_ACTIVE_NEG),
+    (XI_GPO_MODE::XI_GPO_FRAME_TRIGGER_WAIT, XI_GPO_MODE::XI_GPO_FRAME_TRIGGER_WAIT_NEG),
+    (XI_GPO_MODE::XI_GPO_EXPOSURE_PULSE, XI_GPO_MODE::XI_GPO_EXPOSURE_PULSE_NEG),
+    (XI_GPO_MODE::XI_GPO_BUSY, XI_GPO_MODE::XI_GPO_BUSY_NEG),
+    (
+        XI_GPO_MODE::XI_GPO_EXPOSURE_ACTIVE_FIRST_ROW,
+        XI_GPO_MODE::XI_GPO_EXPOSURE_ACTIVE_FIRST_ROW_NEG,
+    ),
+    (
+        XI_GPO_MODE::XI_GPO_EXPOSURE_ACTIVE_ALL_ROWS,
+        XI_GPO_MODE::XI_GPO_EXPOSURE_ACTIVE_ALL_ROWS_NEG,
+    ),
+];
+
+/// Get the GPO mode that outputs the signal of `mode` with the given polarity.
+///
+/// Returns `XI_WRONG_PARAM_VALUE` for modes that can not be inverted.
+pub fn gpo_mode_with_polarity(
+    mode: XI_GPO_MODE::Type,
+    polarity: GpoPolarity,
+) -> Result<XI_GPO_MODE::Type, XI_RETURN> {
+    let (high, low) = GPO_MODE_PAIRS
+        .iter()
+        .copied()
+        .find(|&(high, low)| mode == high || mode == low)
+        .ok_or(XI_RET::XI_WRONG_PARAM_VALUE as XI_RETURN)?;
+    Ok(match polarity {
+        GpoPolarity::ActiveHigh => high,
+        GpoPolarity::ActiveLow => low,
+    })
+}
+
+/// A general purpose input of a camera.
+///
+/// Created by [Camera::gpi]. Every call selects the input and restores the previous
+/// [Camera::gpi_selector] afterwards.
+pub struct Gpi<'a> {
+    camera: &'a mut Camera,
+    port: XI_GPI_SELECTOR::Type,
+}
+
+impl Gpi<'_> {
+    /// Port of this input
+    pub fn port(&self) -> XI_GPI_SELECTOR::Type {
+        self.port
+    }
+
+    /// Set the function of this input
+    pub fn set_mode(&mut self, mode: XI_GPI_MODE::Type) -> Result<&mut Self, XI_RETURN> {
+        self.with_selected(|cam| cam.set_gpi_mode(mode))?;
+        Ok(self)
+    }
+
+    /// Function of this input
+    pub fn mode(&mut self) -> Result<XI_GPI_MODE::Type, XI_RETURN> {
+        self.with_selected(|cam| cam.gpi_mode())
+    }
+
+    /// Current level of this input. `true` means high.
+    pub fn level(&mut self) -> Result<bool, XI_RETURN> {
+        self.with_selected(|cam| Ok(cam.gpi_level()? != 0))
+    }
+
+    /// Enable or disable the debounce filter of this input
+    pub fn set_debounce(&mut self, enable: bool) -> Result<&mut Self, XI_RETURN> {
+        let value = match enable {
+            true => XI_SWITCH::XI_ON,
+            false => XI_SWITCH::XI_OFF,
+        };
+        self.with_selected(|cam| cam.set_debounce_en(value))?;
+        Ok(self)
+    }
+
+    /// Check if the debounce filter of this input is enabled
+    pub fn debounce(&mut self) -> Result<bool, XI_RETURN> {
+        self.with_selected(|cam| Ok(cam.debounce_en()? == XI_SWITCH::XI_ON))
+    }
+
+    fn with_selected<R, F>(&mut self, f: F) -> Result<R, XI_RETURN>
+    where
+        F: FnOnce(&mut Camera) -> Result<R, XI_RETURN>,
+    {
+        self.camera.with_selector(XI_PRM_GPI_SELECTOR, self.port, f)
+    }
+}
+
+/// A general purpose output of a camera.
+///
+/// Created by [Camera::gpo]. Every call selects the output and restores the previous
+/// [Camera::gpo_selector] afterwards.
+pub struct Gpo<'a> {
+    camera: &'a mut Camera,
+    port: XI_GPO_SELECTOR::Type,
+}
+
+impl Gpo<'_> {
+    /// Port of this output
+    pub fn port(&self) -> XI_GPO_SELECTOR::Type {
+        self.port
+    }
+
+    /// Set the signal of this output
+    pub fn set_mode(&mut self, mode: XI_GPO_MODE::Type) -> Result<&mut Self, XI_RETURN> {
+        self.with_selected(|cam| cam.set_gpo_mode(mode))?;
+        Ok(self)
+    }
+
+    /// Signal of this output
+    pub fn mode(&mut self) -> Result<XI_GPO_MODE::Type, XI_RETURN> {
+        self.with_selected(|cam| cam.gpo_mode())
+    }
+
+    /// Drive this output to a constant level. `true` means high.
+    pub fn set_level(&mut self, level: bool) -> Result<&mut Self, XI_RETURN> {
+        match level {
+            true => self.set_mode(XI_GPO_MODE::XI_GPO_ON),
+            false => self.set_mode(XI_GPO_MODE::XI_GPO_OFF),
+        }
+    }
+
+    /// Drive this output high for the given duration and low afterwards.
+    ///
+    /// The pulse is timed on the host, so its length is only accurate to a few milliseconds.
+    pub fn pulse(&mut self, duration: Duration) -> Result<&mut Self, XI_RETURN> {
+        self.set_level(true)?;
+        sleep(duration);
+        self.set_level(false)
+    }
+
+    /// Set the polarity of the current signal of this output.
+    ///
+    /// Returns `XI_WRONG_PARAM_VALUE` if the current signal can not be inverted.
+    pub fn set_polarity(&mut self, polarity: GpoPolarity) -> Result<&mut Self, XI_RETURN> {
+        let mode = gpo_mode_with_polarity(self.mode()?, polarity)?;
+        self.set_mode(mode)
+    }
+
+    /// Polarity of the current signal of this output.
+    ///
+    /// Returns `XI_WRONG_PARAM_VALUE` for signals without polarity.
+    pub fn polarity(&mut self) -> Result<GpoPolarity, XI_RETURN> {
+        let mode = self.mode()?;
+        match gpo_mode_with_polarity(mode, GpoPolarity::ActiveHigh)? == mode {
+            true => Ok(GpoPolarity::ActiveHigh),
+            false => Ok(GpoPolarity::ActiveLow),
+        }
+    }
+
+    fn with_selected<R, F>(&mut self, f: F) -> Result<R, XI_RETURN>
+    where
+        F: FnOnce(&mut Camera) -> Result<R, XI_RETURN>,
+    {
+        self.camera.with_selector(XI_PRM_GPO_SELECTOR, self.port, f)
+    }
+}
+
+/// Direction of a level change on an input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    /// Change from low to high
+    Rising,
+
+    /// Change from high to low
+    Falling,
+}
+
+/// Level change on an input, reported by [Camera::poll_gpi_edges]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpiEdge {
+    /// Input on which the level changed
+    pub port: XI_GPI_SELECTOR::Type,
+
+    /// Direction of the change
+    pub edge: Edge,
+}
+
+/// Detects edges by comparing the levels of inputs between polls.
+///
+/// Edges that occur between two polls are only detected if the level is different at the time
+/// of the second poll, so the polling interval limits the length of pulses that can be detected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeDetector {
+    ports: Vec<XI_GPI_SELECTOR::Type>,
+    levels: Vec<Option<bool>>,
+}
+
+impl EdgeDetector {
+    /// Create a detector for the given inputs
+    pub fn new(ports: &[XI_GPI_SELECTOR::Type]) -> Self {
+        EdgeDetector {
+            ports: ports.to_vec(),
+            levels: vec![None; ports.len()],
+        }
+    }
+
+    /// Inputs watched by this detector
+    pub fn ports(&self) -> &[XI_GPI_SELECTOR::Type] {
+        &self.ports
+    }
+
+    /// Update the levels of all inputs (in the order of [Self::ports()]) and return the edges
+    /// since the previous update. The first update never reports edges.
+    pub fn update(&mut self, levels: &[bool]) -> Vec<GpiEdge> {
+        let mut edges = Vec::new();
+        for ((&port, previous), &level) in self.ports.iter().zip(&mut self.levels).zip(levels) {
+            match (*previous, level) {
+                (Some(false), true) => edges.push(GpiEdge {
+                    port,
+                    edge: Edge::Rising,
+                }),
+                (Some(true), false) => edges.push(GpiEdge {
+                    port,
+                    edge: Edge::Falling,
+                }),
+                _ => {}
+            }
+            *previous = Some(level);
+        }
+        edges
+    }
+}
+
+/// Configuration of a GPO as part of a [TriggerConfig]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputConfig {
+    /// Output port
+    pub port: XI_GPO_SELECTOR::Type,
+
+    /// Signal on the output
+    pub mode: XI_GPO_MODE::Type,
+
+    /// Polarity of the signal. Ignored for signals that can not be inverted.
+    pub polarity: GpoPolarity,
+}
+
+/// Complete trigger configuration of a camera.
+///
+/// Use [Camera::set_trigger_config] to apply it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriggerConfig {
+    /// Source of the trigger
+    pub source: XI_TRG_SOURCE::Type,
+
+    /// What is started by the trigger
+    pub selector: XI_TRG_SELECTOR::Type,
+
+    /// Whether a trigger is accepted while the previous frame is still being exposed or read out
+    pub overlap: XI_TRG_OVERLAP::Type,
+
+    /// Input that receives the trigger signal for hardware triggers. `None` for software or no
+    /// trigger.
+    pub input: Option<XI_GPI_SELECTOR::Type>,
+
+    /// Enable the debounce filter of the trigger input
+    pub debounce: bool,
+
+    /// Delay between the trigger and the start of the exposure in microseconds
+    pub delay_us: i32,
+
+    /// Outputs that are configured together with the trigger
+    pub outputs: Vec<OutputConfig>,
+}
+
+impl TriggerConfig {
+    /// Configuration for frames triggered by [AcquisitionBuffer::software_trigger].
+    ///
+    /// [AcquisitionBuffer::software_trigger]: crate::AcquisitionBuffer::software_trigger
+    pub fn software() -> Self {
+        TriggerConfig {
+            source: XI_TRG_SOURCE::XI_TRG_SOFTWARE,
+            selector: XI_TRG_SELECTOR::XI_TRG_SEL_FRAME_START,
+            overlap: XI_TRG_OVERLAP::XI_TRG_OVERLAP_OFF,
+            input: None,
+            debounce: false,
+            delay_us: 0,
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Configuration for frames triggered by a rising edge on the given input.
+    pub fn hardware(input: XI_GPI_SELECTOR::Type) -> Self {
+        TriggerConfig {
+            source: XI_TRG_SOURCE::XI_TRG_EDGE_RISING,
+            input: Some(input),
+            ..Self::software()
+        }
+    }
+}
+
+impl Camera {
+    /// Access a general purpose input of the camera.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// use xiapi::XI_GPI_MODE::XI_GPI_TRIGGER;
+    /// let mut cam = xiapi::open_device(None)?;
+    /// cam.gpi(1).set_mode(XI_GPI_TRIGGER)?.set_debounce(true)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn gpi(&mut self, port: XI_GPI_SELECTOR::Type) -> Gpi<'_> {
+        Gpi { camera: self, port }
+    }
+
+    /// Access a general purpose output of the camera.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// use xiapi::GpoPolarity;
+    /// use xiapi::XI_GPO_MODE::XI_GPO_EXPOSURE_ACTIVE;
+    /// let mut cam = xiapi::open_device(None)?;
+    /// cam.gpo(2)
+    ///     .set_mode(XI_GPO_EXPOSURE_ACTIVE)?
+    ///     .set_polarity(GpoPolarity::ActiveLow)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn gpo(&mut self, port: XI_GPO_SELECTOR::Type) -> Gpo<'_> {
+        Gpo { camera: self, port }
+    }
+
+    /// Current level of a general purpose input. `true` means high.
+    pub fn gpi_level_of(&mut self, port: XI_GPI_SELECTOR::Type) -> Result<bool, XI_RETURN> {
+        self.gpi(port).level()
+    }
+
+    /// Drive a general purpose output to a constant level. `true` means high.
+    pub fn set_gpo_level(&mut self, port: XI_GPO_SELECTOR::Type, level: bool) -> Result<(), XI_RETURN> {
+        self.gpo(port).set_level(level)?;
+        Ok(())
+    }
+
+    /// Emit a high pulse on a general purpose output. See [Gpo::pulse].
+    pub fn gpo_pulse(&mut self, port: XI_GPO_SELECTOR::Type, duration: Duration) -> Result<(), XI_RETURN> {
+        self.gpo(port).pulse(duration)?;
+        Ok(())
+    }
+
+    /// Read the inputs watched by the detector and return the edges since the previous poll.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// let mut cam = xiapi::open_device(None)?;
+    /// let mut detector = xiapi::EdgeDetector::new(&[1, 2]);
+    /// loop {
+    ///     for edge in cam.poll_gpi_edges(&mut detector)? {
+    ///         println!("{:?} edge on input {}", edge.edge, edge.port);
+    ///     }
+    ///     std::thread::sleep(std::time::Duration::from_millis(10));
+    /// #   break;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn poll_gpi_edges(&mut self, detector: &mut EdgeDetector) -> Result<Vec<GpiEdge>, XI_RETURN> {
+        let levels = detector
+            .ports()
+            .iter()
+            .map(|&port| self.gpi_level_of(port))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(detector.update(&levels))
+    }
+
+    /// Poll an input until the given edge occurs.
+    ///
+    /// Returns `XI_TIMEOUT` if no edge was detected within `timeout`.
+    pub fn wait_for_gpi_edge(
+        &mut self,
+        port: XI_GPI_SELECTOR::Type,
+        edge: Edge,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<(), XI_RETURN> {
+        let start = Instant::now();
+        let mut detector = EdgeDetector::new(&[port]);
+        loop {
+            if self.poll_gpi_edges(&mut detector)?.iter().any(|e| e.edge == edge) {
+                return Ok(());
+            }
+            if start.elapsed() >= timeout {
+                return Err(XI_RET::XI_TIMEOUT as XI_RETURN);
+            }
+            sleep(poll_interval);
+        }
+    }
+
+    /// Apply a complete trigger configuration and verify it.
+    ///
+    /// The trigger is disabled while the configuration is changed and the trigger source is set
+    /// last. All values are read back afterwards and `XI_WRONG_PARAM_VALUE` is returned if the
+    /// camera did not accept one of them. A delay of zero is not applied on cameras that do not
+    /// support a trigger delay.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// use xiapi::{GpoPolarity, OutputConfig, TriggerConfig};
+    /// use xiapi::XI_GPO_MODE::XI_GPO_EXPOSURE_ACTIVE;
+    /// let mut cam = xiapi::open_device(None)?;
+    /// let mut config = TriggerConfig::hardware(1);
+    /// config.delay_us = 100;
+    /// config.outputs.push(OutputConfig {
+    ///     port: 1,
+    ///     mode: XI_GPO_EXPOSURE_ACTIVE,
+    ///     polarity: GpoPolarity::ActiveHigh,
+    /// });
+    /// cam.set_trigger_config(&config)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_trigger_config(&mut self, config: &TriggerConfig) -> Result<(), XI_RETURN> {
+        let outputs = config
+            .outputs
+            .iter()
+            .map(|output| {
+                let mode = gpo_mode_with_polarity(output.mode, output.polarity).unwrap_or(output.mode);
+                (output.port, mode)
+            })
+            .collect::<Vec<_>>();
+
+        self.set_trg_source(XI_TRG_SOURCE::XI_TRG_OFF)?;
+        self.set_trg_selector(config.selector)?;
+        self.set_trg_overlap(config.overlap)?;
+        let delay_supported = match self.set_trg_delay(config.delay_us) {
+            Ok(()) => true,
+            Err(_) if config.delay_us == 0 => false,
+            Err(err) => return Err(err),
+        };
+        if let Some(input) = config.input {
+            self.gpi(input)
+                .set_mode(XI_GPI_MODE::XI_GPI_TRIGGER)?
+                .set_debounce(config.debounce)?;
+        }
+        for &(port, mode) in &outputs {
+            self.gpo(port).set_mode(mode)?;
+        }
+        self.set_trg_source(config.source)?;
+
+        let mut valid = self.trg_source()? == config.source
+            && self.trg_selector()? == config.selector
+            && self.trg_overlap()? == config.overlap
+            && (!delay_supported || self.trg_delay()? == config.delay_us);
+        if let Some(input) = config.input {
+            let mut gpi = self.gpi(input);
+            valid &= gpi.mode()? == XI_GPI_MODE::XI_GPI_TRIGGER && gpi.debounce()? == config.debounce;
+        }
+        for &(port, mode) in &outputs {
+            valid &= self.gpo(port).mode()? == mode;
+        }
+        match valid {
+            true => Ok(()),
+            false => Err(XI_RET::XI_WRONG_PARAM_VALUE as XI_RETURN),
+        }
+    }
+}
+
+impl AcquisitionBuffer {
+    /// Current level of a general purpose input. See [Camera::gpi_level_of].
+    pub fn gpi_level_of(&mut self, port: XI_GPI_SELECTOR::Type) -> Result<bool, XI_RETURN> {
+        self.camera.gpi_level_of(port)
+    }
+
+    /// Drive a general purpose output to a constant level. See [Camera::set_gpo_level].
+    pub fn set_gpo_level(&mut self, port: XI_GPO_SELECTOR::Type, level: bool) -> Result<(), XI_RETURN> {
+        self.camera.set_gpo_level(port, level)
+    }
+
+    /// Emit a high pulse on a general purpose output. See [Camera::gpo_pulse].
+    pub fn gpo_pulse(&mut self, port: XI_GPO_SELECTOR::Type, duration: Duration) -> Result<(), XI_RETURN> {
+        self.camera.gpo_pulse(port, duration)
+    }
+
+    /// Read the inputs watched by the detector and return the edges since the previous poll.
+    /// See [Camera::poll_gpi_edges].
+    pub fn poll_gpi_edges(&mut self, detector: &mut EdgeDetector) -> Result<Vec<GpiEdge>, XI_RETURN> {
+        self.camera.poll_gpi_edges(detector)
+    }
+
+    /// Poll an input until the given edge occurs. See [Camera::wait_for_gpi_edge].
+    pub fn wait_for_gpi_edge(
+        &mut self,
+        port: XI_GPI_SELECTOR::Type,
+        edge: Edge,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<(), XI_RETURN> {
+        self.camera.wait_for_gpi_edge(port, edge, timeout, poll_interval)
+    }
+}
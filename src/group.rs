@@ -0,0 +1,413 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use xiapi_sys::*;
+
+use crate::open_device_by_serial;
+use crate::AcquisitionBuffer;
+use crate::Camera;
+use crate::OwnedImage;
+
+/// Wiring of the trigger signal between the cameras of a [CameraGroup].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyncConfig {
+    /// Output of the master camera that is connected to the slave cameras
+    pub master_output: XI_GPO_SELECTOR::Type,
+
+    /// Signal on the master output. The default is the exposure active signal, so that the slaves
+    /// start their exposure together with the master.
+    pub master_output_mode: XI_GPO_MODE::Type,
+
+    /// Input of the slave cameras that is connected to the master
+    pub slave_input: XI_GPI_SELECTOR::Type,
+
+    /// Edge of the master signal that triggers the slaves
+    pub slave_trigger_source: XI_TRG_SOURCE::Type,
+
+    /// Time in milliseconds to wait for the frames of the slaves once the master frame was
+    /// received. If no matching frame arrives in time, the frame is reported as missing.
+    pub partner_timeout: u32,
+
+    /// Maximum difference, relative to the time between two master frames, between the time that
+    /// passed on a slave and on the master since their last matching frames. A slave frame
+    /// outside this tolerance belongs to a different trigger, e.g. because the slave missed a
+    /// trigger, and the frame numbering of the slave is realigned. `None` matches frames by their
+    /// frame number only. The check requires timestamps with the same unit on all cameras, i.e.
+    /// cameras of the same series.
+    pub timing_tolerance: Option<f32>,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        SyncConfig {
+            master_output: XI_GPO_SELECTOR::XI_GPO_PORT1,
+            master_output_mode: XI_GPO_MODE::XI_GPO_EXPOSURE_ACTIVE,
+            slave_input: XI_GPI_SELECTOR::XI_GPI_PORT1,
+            slave_trigger_source: XI_TRG_SOURCE::XI_TRG_EDGE_RISING,
+            partner_timeout: 100,
+            timing_tolerance: Some(0.5),
+        }
+    }
+}
+
+/// Group of cameras that capture frames synchronized by a hardware trigger.
+///
+/// One camera is the master. It acquires frames with its own trigger settings (free run by
+/// default) and outputs a signal on one of its GPOs for every frame. All other cameras are slaves
+/// that are triggered by this signal on one of their GPIs.
+///
+/// Like [Camera], the group has to be mutable to change parameters and is consumed while the
+/// acquisition is running.
+pub struct CameraGroup {
+    master: Camera,
+    slaves: Vec<Camera>,
+    config: SyncConfig,
+}
+
+impl CameraGroup {
+    /// Create a group from cameras that are already open and configure their inputs and outputs.
+    pub fn new(
+        mut master: Camera,
+        mut slaves: Vec<Camera>,
+        config: SyncConfig,
+    ) -> Result<Self, XI_RETURN> {
+        master
+            .gpo(config.master_output)
+            .set_mode(config.master_output_mode)?;
+        for slave in &mut slaves {
+            slave.gpi(config.slave_input).set_mode(XI_GPI_MODE::XI_GPI_TRIGGER)?;
+            slave.set_trg_source(config.slave_trigger_source)?;
+        }
+        Ok(CameraGroup {
+            master,
+            slaves,
+            config,
+        })
+    }
+
+    /// Open the cameras with the given serial numbers and configure them as a group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// let group = xiapi::CameraGroup::open("12345678", &["23456789"], Default::default())?;
+    /// let mut acq = group.start_acquisition::<u8>()?;
+    /// let frames = acq.next_frames(None)?;
+    /// if !frames.is_complete() {
+    ///     println!("Frame {} is missing partners", frames.frame_number());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn open(
+        master_serial: &str,
+        slave_serials: &[&str],
+        config: SyncConfig,
+    ) -> Result<Self, XI_RETURN> {
+        let master = open_device_by_serial(master_serial)?;
+        let slaves = slave_serials
+            .iter()
+            .map(|serial| open_device_by_serial(serial))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(master, slaves, config)
+    }
+
+    /// The master camera
+    pub fn master(&self) -> &Camera {
+        &self.master
+    }
+
+    /// Mutable access to the master camera, e.g. to change its trigger or exposure settings.
+    pub fn master_mut(&mut self) -> &mut Camera {
+        &mut self.master
+    }
+
+    /// The slave cameras in the order they were added to the group
+    pub fn slaves(&self) -> &[Camera] {
+        &self.slaves
+    }
+
+    /// Mutable access to the slave cameras
+    pub fn slaves_mut(&mut self) -> &mut [Camera] {
+        &mut self.slaves
+    }
+
+    /// Start the acquisition on all cameras.
+    ///
+    /// The slaves are started first, so that they are ready when the master sends the first
+    /// trigger. The pixel type is used for all frames of this acquisition.
+    pub fn start_acquisition<T: Clone>(self) -> Result<GroupAcquisition<T>, XI_RETURN> {
+        let slaves = self
+            .slaves
+            .into_iter()
+            .map(Camera::start_acquisition)
+            .collect::<Result<Vec<_>, _>>()?;
+        let sync = slaves.iter().map(|_| SlaveSync::default()).collect();
+        let master = self.master.start_acquisition()?;
+        Ok(GroupAcquisition {
+            master,
+            slaves,
+            sync,
+            last_master: None,
+            config: self.config,
+        })
+    }
+}
+
+/// Frames of all cameras in a [CameraGroup] that belong to the same trigger.
+pub struct FrameSet<T> {
+    frame_number: u32,
+    master: OwnedImage<T>,
+    slaves: Vec<Option<OwnedImage<T>>>,
+    realigned: Vec<usize>,
+}
+
+impl<T> FrameSet<T> {
+    /// Acquisition frame number of the master frame
+    pub fn frame_number(&self) -> u32 {
+        self.frame_number
+    }
+
+    /// Frame of the master camera
+    pub fn master(&self) -> &OwnedImage<T> {
+        &self.master
+    }
+
+    /// Frames of the slave cameras. Frames that did not arrive are `None`.
+    pub fn slaves(&self) -> &[Option<OwnedImage<T>>] {
+        &self.slaves
+    }
+
+    /// Check if the frames of all slaves are present
+    pub fn is_complete(&self) -> bool {
+        self.slaves.iter().all(Option::is_some)
+    }
+
+    /// Indices of the slaves whose frames are missing
+    pub fn missing(&self) -> Vec<usize> {
+        (0..self.slaves.len())
+            .filter(|&i| self.slaves[i].is_none())
+            .collect()
+    }
+
+    /// Indices of the slaves whose frame numbering was found to be shifted against the master
+    /// and was realigned while collecting this set. The frames of these slaves are missing in
+    /// this set, see [SyncConfig::timing_tolerance].
+    pub fn realigned(&self) -> &[usize] {
+        &self.realigned
+    }
+}
+
+/// Running acquisition of a [CameraGroup].
+///
+/// Created by [CameraGroup::start_acquisition].
+pub struct GroupAcquisition<T> {
+    master: AcquisitionBuffer,
+    slaves: Vec<AcquisitionBuffer>,
+    sync: Vec<SlaveSync<T>>,
+    last_master: Option<(u32, u64)>,
+    config: SyncConfig,
+}
+
+impl<T: Clone> GroupAcquisition<T> {
+    /// Get the next set of frames.
+    ///
+    /// Waits up to `timeout` milliseconds for the next master frame. Frames of the slaves are
+    /// matched by their acquisition frame number, which counts the triggers received since the
+    /// acquisition was started. Slave frames that were skipped by the master are dropped and
+    /// slave frames that did not arrive within [SyncConfig::partner_timeout] are reported as
+    /// missing in the returned [FrameSet]. If a slave missed a trigger, its frame numbers are
+    /// realigned with the timestamps of the frames and the set reports it in
+    /// [FrameSet::realigned()].
+    pub fn next_frames(&mut self, timeout: Option<u32>) -> Result<FrameSet<T>, XI_RETURN> {
+        let master = self.master.next_image::<T>(timeout)?.to_owned_image();
+        let frame_number = master.image().acq_nframe();
+        let timestamp = master.image().timestamp_raw();
+        let timing = match (
+            self.last_master.replace((frame_number, timestamp)),
+            self.config.timing_tolerance,
+        ) {
+            (Some((last_number, last_timestamp)), Some(tolerance))
+                if frame_number > last_number =>
+            {
+                let period = timestamp.saturating_sub(last_timestamp) as f64
+                    / (frame_number - last_number) as f64;
+                Some(MasterTiming {
+                    timestamp,
+                    period,
+                    tolerance: tolerance as f64,
+                })
+            }
+            _ => None,
+        };
+        let mut slaves = Vec::with_capacity(self.slaves.len());
+        let mut realigned = Vec::new();
+        for (i, (buffer, sync)) in self.slaves.iter().zip(&mut self.sync).enumerate() {
+            let partner_timeout = Some(self.config.partner_timeout);
+            let (partner, shifted) = sync.next_partner(frame_number, timestamp, timing, || {
+                Ok(buffer.next_image::<T>(partner_timeout)?.to_owned_image())
+            })?;
+            if shifted {
+                realigned.push(i);
+            }
+            slaves.push(partner);
+        }
+        Ok(FrameSet {
+            frame_number,
+            master,
+            slaves,
+            realigned,
+        })
+    }
+
+    /// The acquisition buffer of the master camera
+    pub fn master(&self) -> &AcquisitionBuffer {
+        &self.master
+    }
+
+    /// The acquisition buffers of the slave cameras
+    pub fn slaves(&self) -> &[AcquisitionBuffer] {
+        &self.slaves
+    }
+
+    /// Stop the acquisition on all cameras and return the group.
+    ///
+    /// The master is stopped first so that no further triggers are sent to the slaves.
+    pub fn stop_acquisition(self) -> Result<CameraGroup, XI_RETURN> {
+        let master = self.master.stop_acquisition()?;
+        let slaves = self
+            .slaves
+            .into_iter()
+            .map(AcquisitionBuffer::stop_acquisition)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(CameraGroup {
+            master,
+            slaves,
+            config: self.config,
+        })
+    }
+}
+
+/// Timing of the current master frame, used to check the timestamps of slave frames
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MasterTiming {
+    pub(crate) timestamp: u64,
+    pub(crate) period: f64,
+    pub(crate) tolerance: f64,
+}
+
+/// Matching state of the frame stream of one slave camera
+pub(crate) struct SlaveSync<T> {
+    pending: Option<OwnedImage<T>>,
+    /// Difference between the frame numbers of the slave and the master for the same trigger
+    offset: i64,
+    /// Timestamps of the master and the slave frame of the last matching set
+    reference: Option<(u64, u64)>,
+}
+
+impl<T> Default for SlaveSync<T> {
+    fn default() -> Self {
+        SlaveSync {
+            pending: None,
+            offset: 0,
+            reference: None,
+        }
+    }
+}
+
+impl<T> SlaveSync<T> {
+    /// Find the slave frame that belongs to the given master frame.
+    ///
+    /// Returns the frame, or `None` if it is missing, and whether the frame numbering of the
+    /// slave had to be realigned.
+    pub(crate) fn next_partner<F>(
+        &mut self,
+        frame_number: u32,
+        timestamp: u64,
+        timing: Option<MasterTiming>,
+        mut next_frame: F,
+    ) -> Result<(Option<OwnedImage<T>>, bool), XI_RETURN>
+    where
+        F: FnMut() -> Result<OwnedImage<T>, XI_RETURN>,
+    {
+        let mut realigned = false;
+        loop {
+            let expected = frame_number as i64 + self.offset;
+            if expected < 0 {
+                return Ok((None, realigned));
+            }
+            let frame = match find_partner(&mut self.pending, expected as u32, &mut next_frame)? {
+                Some(frame) => frame,
+                None => return Ok((None, realigned)),
+            };
+            let slave_timestamp = frame.image().timestamp_raw();
+            let shift = match (timing, self.reference) {
+                (Some(timing), Some((master_reference, slave_reference)))
+                    if timing.period > 0.0 =>
+                {
+                    let master_elapsed = timing.timestamp as f64 - master_reference as f64;
+                    let slave_elapsed = slave_timestamp as f64 - slave_reference as f64;
+                    let deviation = (slave_elapsed - master_elapsed) / timing.period;
+                    match deviation.abs() > timing.tolerance {
+                        true => deviation.round() as i64,
+                        false => 0,
+                    }
+                }
+                _ => 0,
+            };
+            match shift {
+                0 => {
+                    self.reference = Some((timestamp, slave_timestamp));
+                    return Ok((Some(frame), realigned));
+                }
+                // The frame was triggered later, so the slave missed a trigger. It is kept for
+                // the master frame it belongs to.
+                shift if shift > 0 => {
+                    self.offset -= shift;
+                    self.pending = Some(frame);
+                    return Ok((None, true));
+                }
+                // The frame was triggered earlier, so it is dropped
+                shift => {
+                    self.offset -= shift;
+                    realigned = true;
+                }
+            }
+        }
+    }
+}
+
+/// Find the frame with the given frame number in the stream of a slave camera.
+///
+/// Frames with a lower frame number are dropped. A frame with a higher number is kept in
+/// `pending` for the next frame set. A timeout is reported as missing frame.
+pub(crate) fn find_partner<T, F>(
+    pending: &mut Option<OwnedImage<T>>,
+    frame_number: u32,
+    mut next_frame: F,
+) -> Result<Option<OwnedImage<T>>, XI_RETURN>
+where
+    F: FnMut() -> Result<OwnedImage<T>, XI_RETURN>,
+{
+    loop {
+        let frame = match pending.take() {
+            Some(frame) => frame,
+            None => match next_frame() {
+                Ok(frame) => frame,
+                Err(err) if err == XI_RET::XI_TIMEOUT as XI_RETURN => return Ok(None),
+                Err(err) => return Err(err),
+            },
+        };
+        let number = frame.image().acq_nframe();
+        if number == frame_number {
+            return Ok(Some(frame));
+        }
+        if number > frame_number {
+            *pending = Some(frame);
+            return Ok(None);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use xiapi_sys::*;
+
+use crate::Camera;
+use crate::Image;
+
+/// A single kneepoint of the piecewise linear response (PWLR) used in sensor HDR mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HdrKneepoint {
+    /// Position of the kneepoint in percent of the exposure time
+    pub time: i32,
+
+    /// Value of the kneepoint in percent of the sensor saturation
+    pub level: i32,
+}
+
+/// Configuration of the in-sensor HDR mode.
+///
+/// The sensor limits the charge of each pixel to the level of the first kneepoint until the time
+/// of the first kneepoint is reached. After that, the limit is raised to the level of the second
+/// kneepoint (if any) and finally to full saturation for the rest of the exposure.
+/// Bright pixels therefore only integrate light during the later part of the exposure which
+/// results in a piecewise linear response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HdrConfig {
+    /// First kneepoint
+    pub kneepoint1: HdrKneepoint,
+
+    /// Optional second kneepoint. Must be later and higher than the first kneepoint.
+    pub kneepoint2: Option<HdrKneepoint>,
+}
+
+impl HdrConfig {
+    /// Number of kneepoints used by this configuration
+    pub fn kneepoint_count(&self) -> i32 {
+        match self.kneepoint2 {
+            None => 1,
+            Some(_) => 2,
+        }
+    }
+
+    /// Check that the kneepoints describe a valid, monotonic response.
+    ///
+    /// This only checks the configuration itself. Ranges specific to a camera are checked when
+    /// the configuration is applied with [Camera::set_hdr_config].
+    pub fn validate(&self) -> Result<(), XI_RETURN> {
+        let valid_kneepoint = |kp: &HdrKneepoint| {
+            (1..100).contains(&kp.time) && (1..=100).contains(&kp.level)
+        };
+        if !valid_kneepoint(&self.kneepoint1) {
+            return Err(XI_RET::XI_OUT_OF_RANGE as XI_RETURN);
+        }
+        if let Some(kp2) = &self.kneepoint2 {
+            if !valid_kneepoint(kp2) {
+                return Err(XI_RET::XI_OUT_OF_RANGE as XI_RETURN);
+            }
+            if kp2.time <= self.kneepoint1.time || kp2.level <= self.kneepoint1.level {
+                return Err(XI_RET::XI_WRONG_PARAM_VALUE as XI_RETURN);
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert a pixel value from the piecewise linear response back to a linear scale.
+    ///
+    /// The result is the value the pixel would have had with a linear response and the same
+    /// exposure time. It may therefore be larger than `saturation`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value`: Pixel value with the black level already subtracted.
+    /// * `saturation`: Pixel value at which the sensor saturates (without black level).
+    pub fn linearize(&self, value: f32, saturation: f32) -> f32 {
+        let value = value.clamp(0.0, saturation);
+        // The response is the minimum of one line per segment. As every line is increasing, the
+        // inverse is the maximum of the inverted lines.
+        [Some(self.kneepoint1), self.kneepoint2]
+            .iter()
+            .flatten()
+            .map(|kp| {
+                let offset = kp.level as f32 / 100.0 * saturation;
+                let slope = 1.0 - kp.time as f32 / 100.0;
+                (value - offset) / slope
+            })
+            .fold(value, f32::max)
+    }
+
+    /// Linearize all pixels of an image captured in HDR mode.
+    ///
+    /// The black level of the image is subtracted before the conversion. The returned values are
+    /// stored row by row without any padding. Planar images are converted to the interleaved
+    /// format first.
+    pub fn linearize_image<T>(&self, image: &Image<T>) -> Vec<f32>
+    where
+        T: Copy + Into<f32>,
+    {
+        if image.is_planar() {
+            return self.linearize_image(&image.to_interleaved().image());
+        }
+        let black_level = image.black_level() as f32;
+        let saturation = image.data_saturation() as f32 - black_level;
+        let mut result = Vec::with_capacity(image.width() as usize * image.height() as usize);
+        for y in 0..image.height() as usize {
+            if let Some(row) = image.row(y) {
+                result.extend(
+                    row.iter()
+                        .map(|&pixel| self.linearize(pixel.into() - black_level, saturation)),
+                );
+            }
+        }
+        result
+    }
+}
+
+impl Camera {
+    /// Enable the sensor HDR mode with the given kneepoint configuration.
+    ///
+    /// The configuration is checked against the ranges reported by the camera before anything
+    /// is written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// use xiapi::{HdrConfig, HdrKneepoint};
+    /// let mut cam = xiapi::open_device(None)?;
+    /// let config = HdrConfig {
+    ///     kneepoint1: HdrKneepoint { time: 60, level: 40 },
+    ///     kneepoint2: Some(HdrKneepoint { time: 90, level: 80 }),
+    /// };
+    /// cam.set_hdr_config(&config)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_hdr_config(&mut self, config: &HdrConfig) -> Result<(), XI_RETURN> {
+        config.validate()?;
+        let mut values: Vec<(&'static [u8], i32)> = vec![
+            (XI_PRM_HDR_KNEEPOINT_COUNT, config.kneepoint_count()),
+            (XI_PRM_HDR_T1, config.kneepoint1.time),
+            (XI_PRM_KNEEPOINT1, config.kneepoint1.level),
+        ];
+        if let Some(kp2) = config.kneepoint2 {
+            values.push((XI_PRM_HDR_T2, kp2.time));
+            values.push((XI_PRM_KNEEPOINT2, kp2.level));
+        }
+        for &(param, value) in &values {
+            let (min, max) = unsafe { (self.param_min::<i32>(param)?, self.param_max::<i32>(param)?) };
+            if !(min..=max).contains(&value) {
+                return Err(XI_RET::XI_OUT_OF_RANGE as XI_RETURN);
+            }
+        }
+        unsafe {
+            for (param, value) in values {
+                self.set_param(param, value)?;
+            }
+            self.set_param(XI_PRM_HDR, XI_SWITCH::XI_ON)
+        }
+    }
+
+    /// Disable the sensor HDR mode.
+    pub fn disable_hdr(&mut self) -> Result<(), XI_RETURN> {
+        unsafe { self.set_param(XI_PRM_HDR, XI_SWITCH::XI_OFF) }
+    }
+
+    /// Current HDR configuration of the camera.
+    ///
+    /// Returns `None` if the HDR mode is disabled.
+    pub fn hdr_config(&self) -> Result<Option<HdrConfig>, XI_RETURN> {
+        unsafe {
+            if self.param::<XI_SWITCH::Type>(XI_PRM_HDR)? == XI_SWITCH::XI_OFF {
+                return Ok(None);
+            }
+            let kneepoint1 = HdrKneepoint {
+                time: self.param(XI_PRM_HDR_T1)?,
+                level: self.param(XI_PRM_KNEEPOINT1)?,
+            };
+            let kneepoint2 = match self.param::<i32>(XI_PRM_HDR_KNEEPOINT_COUNT)? {
+                1 => None,
+                _ => Some(HdrKneepoint {
+                    time: self.param(XI_PRM_HDR_T2)?,
+                    level: self.param(XI_PRM_KNEEPOINT2)?,
+                }),
+            };
+            Ok(Some(HdrConfig {
+                kneepoint1,
+                kneepoint2,
+            }))
+        }
+    }
+}
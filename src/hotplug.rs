@@ -0,0 +1,288 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use std::collections::BTreeSet;
+use std::ops::Deref;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use std::time::Instant;
+
+use xiapi_sys::*;
+
+use crate::camera::device_info_string;
+use crate::number_devices;
+use crate::AcquisitionBuffer;
+use crate::Camera;
+use crate::ErrorClass;
+use crate::OwnedImage;
+use crate::RecoveryPoint;
+
+/// Read the serial number of a camera without opening it.
+pub fn device_serial_number(dev_id: u32) -> Result<String, XI_RETURN> {
+    device_info_string(dev_id, XI_PRM_DEVICE_SN)
+}
+
+/// Serial numbers of all connected cameras.
+pub fn device_serial_numbers() -> Result<Vec<String>, XI_RETURN> {
+    (0..number_devices()?).map(device_serial_number).collect()
+}
+
+/// Change of the set of connected cameras
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceEvent {
+    /// A camera with the given serial number was connected
+    Arrived(String),
+
+    /// A camera with the given serial number was disconnected
+    Removed(String),
+}
+
+/// Detects cameras that are connected or disconnected by comparing the enumerated devices.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceWatcher {
+    known: BTreeSet<String>,
+}
+
+impl DeviceWatcher {
+    /// Create a watcher that knows the cameras that are currently connected.
+    pub fn new() -> Result<Self, XI_RETURN> {
+        let mut watcher = DeviceWatcher::default();
+        watcher.update(device_serial_numbers()?);
+        Ok(watcher)
+    }
+
+    /// Serial numbers of the cameras that were connected at the last poll
+    pub fn devices(&self) -> impl Iterator<Item = &str> {
+        self.known.iter().map(String::as_str)
+    }
+
+    /// Enumerate the connected cameras and return the changes since the last poll.
+    pub fn poll(&mut self) -> Result<Vec<DeviceEvent>, XI_RETURN> {
+        Ok(self.update(device_serial_numbers()?))
+    }
+
+    /// Replace the set of known cameras and return the changes.
+    pub fn update<I: IntoIterator<Item = String>>(&mut self, serial_numbers: I) -> Vec<DeviceEvent> {
+        let current: BTreeSet<String> = serial_numbers.into_iter().collect();
+        let removed = self
+            .known
+            .difference(&current)
+            .cloned()
+            .map(DeviceEvent::Removed);
+        let arrived = current
+            .difference(&self.known)
+            .cloned()
+            .map(DeviceEvent::Arrived);
+        let events = removed.chain(arrived).collect();
+        self.known = current;
+        events
+    }
+
+    /// Poll in a background thread and send all changes to the returned [DeviceEvents].
+    ///
+    /// Failed enumerations are skipped. The thread is stopped when the [DeviceEvents] are
+    /// dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let events = xiapi::DeviceWatcher::new()?.watch(std::time::Duration::from_secs(1));
+    /// for event in events.iter() {
+    ///     println!("{:?}", event);
+    /// }
+    /// # Ok::<(), xiapi::XI_RETURN>(())
+    /// ```
+    pub fn watch(mut self, interval: Duration) -> DeviceEvents {
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let thread = thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                // Parking is interrupted when the events are dropped
+                let deadline = Instant::now() + interval;
+                while !stopped.load(Ordering::Relaxed) && Instant::now() < deadline {
+                    thread::park_timeout(deadline.saturating_duration_since(Instant::now()));
+                }
+                if stopped.load(Ordering::Relaxed) {
+                    return;
+                }
+                let events = match self.poll() {
+                    Ok(events) => events,
+                    Err(_) => continue,
+                };
+                for event in events {
+                    if sender.send(event).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        DeviceEvents {
+            receiver,
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+/// Changes of the connected cameras, sent by the thread started with [DeviceWatcher::watch()].
+///
+/// Events are received with the methods of [Receiver]. Dropping this stops and joins the thread.
+pub struct DeviceEvents {
+    receiver: Receiver<DeviceEvent>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Deref for DeviceEvents {
+    type Target = Receiver<DeviceEvent>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.receiver
+    }
+}
+
+impl Drop for DeviceEvents {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Camera that is reopened automatically when it is disconnected.
+///
+/// The camera is opened again by its serial number with the configuration of its
+/// [RecoveryPoint] and the acquisition is resumed. Images are returned as [OwnedImage], as the
+/// acquisition buffer changes when the camera is reopened.
+pub struct SupervisedCamera {
+    acquisition: Option<AcquisitionBuffer>,
+    recovery_point: RecoveryPoint,
+    reconnect_timeout: Duration,
+    poll_interval: Duration,
+    reconnects: u32,
+}
+
+impl SupervisedCamera {
+    /// Start the acquisition on a configured camera and supervise it.
+    ///
+    /// If the camera is lost, reconnecting is tried for up to `reconnect_timeout`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// use std::time::Duration;
+    /// let mut cam = xiapi::open_device(None)?;
+    /// cam.set_exposure(5000.0)?;
+    /// let mut supervised = xiapi::SupervisedCamera::start(cam, Duration::from_secs(30))?;
+    /// loop {
+    ///     let image = supervised.next_image::<u8>(None)?;
+    ///     println!("Frame {}, reconnected {} times", image.image().nframe(), supervised.reconnects());
+    /// #   break;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start(camera: Camera, reconnect_timeout: Duration) -> Result<Self, XI_RETURN> {
+        let recovery_point = camera.recovery_point()?;
+        Ok(SupervisedCamera {
+            acquisition: Some(camera.start_acquisition()?),
+            recovery_point,
+            reconnect_timeout,
+            poll_interval: Duration::from_millis(500),
+            reconnects: 0,
+        })
+    }
+
+    /// Get the next image.
+    ///
+    /// Timeouts are returned to the caller. Other errors related to the connection are recovered
+    /// from. `XI_NO_DEVICES_FOUND` is returned if the camera could not be reopened within the
+    /// reconnect timeout.
+    pub fn next_image<T: Clone>(&mut self, timeout: Option<u32>) -> Result<OwnedImage<T>, XI_RETURN> {
+        loop {
+            let acquisition = self.acquisition()?;
+            let err = match acquisition.next_image::<T>(timeout) {
+                Ok(image) => return Ok(image.to_owned_image()),
+                Err(err) => err,
+            };
+            match ErrorClass::of(err) {
+                ErrorClass::Transient | ErrorClass::Other => return Err(err),
+                ErrorClass::RestartAcquisition => acquisition.restart_acquisition()?,
+                ErrorClass::DeviceLost => {
+                    self.acquisition = None;
+                    self.reconnect()?;
+                }
+            }
+        }
+    }
+
+    /// The running acquisition, e.g. to change parameters.
+    ///
+    /// Call [Self::save_configuration()] afterwards to keep the changes after a reconnect.
+    /// Returns `XI_NO_DEVICES_FOUND` if the camera is currently disconnected.
+    pub fn acquisition(&mut self) -> Result<&mut AcquisitionBuffer, XI_RETURN> {
+        self.acquisition
+            .as_mut()
+            .ok_or(XI_RET::XI_NO_DEVICES_FOUND as XI_RETURN)
+    }
+
+    /// Store the current parameters of the camera to apply them after a reconnect.
+    pub fn save_configuration(&mut self) -> Result<(), XI_RETURN> {
+        self.recovery_point = self.acquisition()?.recovery_point()?;
+        Ok(())
+    }
+
+    /// Number of times the camera was reopened
+    pub fn reconnects(&self) -> u32 {
+        self.reconnects
+    }
+
+    /// Set the interval in which the device enumeration is polled while the camera is
+    /// disconnected.
+    pub fn set_poll_interval(&mut self, interval: Duration) {
+        self.poll_interval = interval;
+    }
+
+    /// Stop the acquisition and return the camera.
+    pub fn stop(mut self) -> Result<Camera, XI_RETURN> {
+        match self.acquisition.take() {
+            Some(acquisition) => acquisition.stop_acquisition(),
+            None => Err(XI_RET::XI_NO_DEVICES_FOUND as XI_RETURN),
+        }
+    }
+
+    fn reconnect(&mut self) -> Result<(), XI_RETURN> {
+        let start = Instant::now();
+        while start.elapsed() < self.reconnect_timeout {
+            let serial_number = self.recovery_point.serial_number();
+            let connected = device_serial_numbers()
+                .map(|serials| serials.iter().any(|serial| serial == serial_number))
+                .unwrap_or(false);
+            if connected {
+                if let Ok(acquisition) = self
+                    .recovery_point
+                    .reopen()
+                    .and_then(Camera::start_acquisition)
+                {
+                    self.acquisition = Some(acquisition);
+                    self.reconnects += 1;
+                    return Ok(());
+                }
+            }
+            thread::sleep(self.poll_interval);
+        }
+        Err(XI_RET::XI_NO_DEVICES_FOUND as XI_RETURN)
+    }
+}
@@ -9,6 +9,81 @@ use std::slice::from_raw_parts;
 use image::{ImageBuffer, Pixel};
 
 use xiapi_sys::XI_IMG;
+use xiapi_sys::XI_IMG_FORMAT;
+
+/// Numeric type of the values in an image.
+///
+/// This is used by processing functions that need to convert pixel values to and from floating
+/// point numbers.
+pub trait PixelValue: Copy + Default {
+    /// Largest value that can be stored
+    const MAX_VALUE: f32;
+
+    /// Convert the value to a float
+    fn to_f32(self) -> f32;
+
+    /// Convert from a float, rounding and clamping to the range of the type
+    fn from_f32(value: f32) -> Self;
+}
+
+impl PixelValue for u8 {
+    const MAX_VALUE: f32 = u8::MAX as f32;
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value.round().clamp(0.0, Self::MAX_VALUE) as u8
+    }
+}
+
+impl PixelValue for u16 {
+    const MAX_VALUE: f32 = u16::MAX as f32;
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value.round().clamp(0.0, Self::MAX_VALUE) as u16
+    }
+}
+
+impl PixelValue for u32 {
+    const MAX_VALUE: f32 = u32::MAX as f32;
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value.round().clamp(0.0, Self::MAX_VALUE) as u32
+    }
+}
+
+/// Float values of `XI_RAW32FLOAT` images are normalized to the range 0.0 to 1.0.
+impl PixelValue for f32 {
+    const MAX_VALUE: f32 = 1.0;
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+}
+
+/// Size of a single sample of the given format in bytes
+pub(crate) fn format_sample_size(format: XI_IMG_FORMAT::Type) -> usize {
+    use XI_IMG_FORMAT::*;
+    match format {
+        XI_MONO16 | XI_RAW16 | XI_RGB48 | XI_RGB64 | XI_RGB16_PLANAR | XI_RAW16X2 | XI_RAW16X4 => 2,
+        XI_RAW32 | XI_RAW32FLOAT => 4,
+        _ => 1,
+    }
+}
 
 /// An Image as it is captured by the camera.
 pub struct Image<'a, T> {
@@ -24,28 +99,57 @@ impl<'a, T> Image<'a, T> {
     /// * `x`: Horizontal coordinate of the requested pixel.
     /// * `y`: Vertical coordinate of the requested pixel.
     ///
-    /// returns: Option<&T> A reference to the pixel
+    /// returns: Option<&T> A reference to the pixel. For images with several channels, this is
+    /// the first channel of the pixel, see [Self::sample()].
     pub fn pixel(&self, x: usize, y: usize) -> Option<&T> {
+        self.sample(x, y, 0)
+    }
+
+    /// Get a single channel of a pixel, for interleaved as well as planar formats.
+    ///
+    /// Channels are in the order of the format, i.e. B, G, R(, A) for RGB24/RGB32/RGB48/RGB64
+    /// and R, G, B for planar formats.
+    pub fn sample(&self, x: usize, y: usize, channel: usize) -> Option<&T> {
         let buffer = self.xi_img.bp as *const u8;
         // Check if uninitialized
         if buffer.is_null() {
             return None;
         }
         // Bounds check
-        if x >= self.xi_img.width as usize || y >= self.xi_img.height as usize {
+        if x >= self.xi_img.width as usize || y >= self.xi_img.height as usize || channel >= self.nb_channels() {
             return None;
         }
-        // stride is the total length of a row in bytes
-        let nb_channels = self.nb_channels();
-        let stride = self.xi_img.width as usize * size_of::<T>() * nb_channels
-            + self.xi_img.padding_x as usize;
-        let offset = (stride * y) + (x * size_of::<T>() * nb_channels);
+        let offset = match self.is_planar() {
+            true => self.plane_size() * channel + self.stride() * y + x * size_of::<T>(),
+            false => self.stride() * y + (x * self.nb_channels() + channel) * size_of::<T>(),
+        };
         unsafe {
             let pixel_pointer = buffer.add(offset) as *const T;
             pixel_pointer.as_ref()
         }
     }
 
+    /// Get one plane of an image in a planar format (`XI_RGB_PLANAR`, `XI_RGB16_PLANAR`),
+    /// including the padding at the end of each line.
+    ///
+    /// Returns `None` for interleaved formats.
+    pub fn plane(&self, channel: usize) -> Option<&[T]> {
+        let buffer = self.xi_img.bp as *const u8;
+        if buffer.is_null() || !self.is_planar() || channel >= self.nb_channels() {
+            return None;
+        }
+        let length = self.plane_size() / size_of::<T>();
+        unsafe {
+            let plane_pointer = buffer.add(self.plane_size() * channel) as *const T;
+            Some(from_raw_parts(plane_pointer, length))
+        }
+    }
+
+    /// Check if the channels of the image are stored in separate planes.
+    pub fn is_planar(&self) -> bool {
+        matches!(self.xi_img.frm, XI_IMG_FORMAT::XI_RGB_PLANAR | XI_IMG_FORMAT::XI_RGB16_PLANAR)
+    }
+
     /// Get the width of this image in pixels
     pub fn width(&self) -> u32 {
         self.xi_img.width
@@ -106,6 +210,21 @@ impl<'a, T> Image<'a, T> {
         self.xi_img.exposure_time_us
     }
 
+    /// Pixel value at which the sensor is saturated
+    pub fn data_saturation(&self) -> f64 {
+        self.xi_img.data_saturation
+    }
+
+    /// Gain in dB that was applied to the image
+    pub fn gain_db(&self) -> f32 {
+        self.xi_img.gain_db
+    }
+
+    /// Color filter array of the sensor for RAW images
+    pub fn color_filter_array(&self) -> xiapi_sys::XI_COLOR_FILTER_ARRAY::Type {
+        self.xi_img.color_filter_array
+    }
+
     /// Aquisition Frame Number. Reset only on acquisition start.
     pub fn acq_nframe(&self) -> u32 {
         self.xi_img.acq_nframe
@@ -133,21 +252,95 @@ impl<'a, T> Image<'a, T> {
                 from_raw_parts(self.xi_img.bp as *const T, length)
             }
             else {
-                let length = self.xi_img.width as usize * self.xi_img.height as usize * self.nb_channels();
+                let length = self.data_size() / size_of::<T>();
                 from_raw_parts(self.xi_img.bp as *const T, length)
             }
         }
     }
 
-    fn nb_channels(&self) -> usize
+    /// Get the raw image data (including padding) as bytes.
+    pub(crate) fn data_bytes(&self) -> &[u8] {
+        if self.xi_img.bp.is_null() {
+            return &[];
+        }
+        let length = match self.xi_img.bp_size {
+            0 => self.data_size(),
+            size => size as usize,
+        };
+        unsafe { from_raw_parts(self.xi_img.bp as *const u8, length) }
+    }
+
+    /// Get a single line of an interleaved image without the padding at its end.
+    pub(crate) fn row(&self, y: usize) -> Option<&[T]> {
+        let buffer = self.xi_img.bp as *const u8;
+        if buffer.is_null() || y >= self.xi_img.height as usize || self.is_planar() {
+            return None;
+        }
+        let length = self.xi_img.width as usize * self.nb_channels();
+        unsafe {
+            let row_pointer = buffer.add(self.stride() * y) as *const T;
+            Some(from_raw_parts(row_pointer, length))
+        }
+    }
+
+    // stride is the total length of a row in bytes, of a single plane for planar formats
+    pub(crate) fn stride(&self) -> usize {
+        let samples_per_pixel = match self.is_planar() {
+            true => 1,
+            false => self.nb_channels(),
+        };
+        self.xi_img.width as usize * size_of::<T>() * samples_per_pixel + self.xi_img.padding_x as usize
+    }
+
+    // Length of a plane in bytes, which is the whole image for interleaved formats
+    fn plane_size(&self) -> usize {
+        self.stride() * self.xi_img.height as usize
+    }
+
+    // Length of the image data in bytes if the camera did not provide it
+    fn data_size(&self) -> usize {
+        match self.is_planar() {
+            true => self.plane_size() * self.nb_channels(),
+            false => self.plane_size(),
+        }
+    }
+
+    /// Pixel value at which the image is saturated.
+    /// Falls back to the maximum value of the format if the camera does not report it.
+    pub(crate) fn saturation_level(&self) -> f32 {
+        match self.xi_img.data_saturation {
+            x if x > 0.0 => x as f32,
+            _ => match self.xi_img.frm {
+                XI_IMG_FORMAT::XI_RAW32FLOAT => 1.0,
+                format => match format_sample_size(format) {
+                    4 => u32::MAX as f32,
+                    2 => u16::MAX as f32,
+                    _ => u8::MAX as f32,
+                },
+            },
+        }
+    }
+
+    pub(crate) fn nb_channels(&self) -> usize
     {
         match self.xi_img.frm {
-            xiapi_sys::XI_IMG_FORMAT::XI_MONO8  => 1,
-            xiapi_sys::XI_IMG_FORMAT::XI_MONO16 => 1,
-            xiapi_sys::XI_IMG_FORMAT::XI_RAW8   => 1,
-            xiapi_sys::XI_IMG_FORMAT::XI_RAW16  => 1,
-            xiapi_sys::XI_IMG_FORMAT::XI_RGB24  => 3,
-            xiapi_sys::XI_IMG_FORMAT::XI_RGB32  => 4,
+            XI_IMG_FORMAT::XI_MONO8       => 1,
+            XI_IMG_FORMAT::XI_MONO16      => 1,
+            XI_IMG_FORMAT::XI_RAW8        => 1,
+            XI_IMG_FORMAT::XI_RAW16       => 1,
+            XI_IMG_FORMAT::XI_RAW32       => 1,
+            XI_IMG_FORMAT::XI_RAW32FLOAT  => 1,
+            XI_IMG_FORMAT::XI_FRM_TRANSPORT_DATA => 1,
+            XI_IMG_FORMAT::XI_RAW8X2      => 2,
+            XI_IMG_FORMAT::XI_RAW16X2     => 2,
+            XI_IMG_FORMAT::XI_RAW8X4      => 4,
+            XI_IMG_FORMAT::XI_RAW16X4     => 4,
+            XI_IMG_FORMAT::XI_RGB24       => 3,
+            XI_IMG_FORMAT::XI_RGB48       => 3,
+            XI_IMG_FORMAT::XI_RGB_PLANAR  => 3,
+            XI_IMG_FORMAT::XI_RGB16_PLANAR => 3,
+            XI_IMG_FORMAT::XI_RGB32       => 4,
+            XI_IMG_FORMAT::XI_RGB64       => 4,
 
             _ => 0,
         }
@@ -159,6 +352,122 @@ unsafe impl<'a,T> Send for Image<'a, T>{
 
 }
 
+impl<'a, T: Clone> Image<'a, T> {
+    /// Copy the image data and metadata into an [OwnedImage].
+    ///
+    /// This is required to keep an image after the next call to [AcquisitionBuffer::next_image]
+    /// (which may reuse the same memory) or after the acquisition was stopped.
+    ///
+    /// [AcquisitionBuffer::next_image]: crate::AcquisitionBuffer::next_image
+    pub fn to_owned_image(&self) -> OwnedImage<T> {
+        let mut xi_img = self.xi_img;
+        xi_img.bp = std::ptr::null_mut();
+        OwnedImage {
+            xi_img,
+            data: Vec::from(self.data()),
+        }
+    }
+
+    /// Convert an image in a planar format to the interleaved format with the same sample size,
+    /// i.e. `XI_RGB_PLANAR` to `XI_RGB24` and `XI_RGB16_PLANAR` to `XI_RGB48` (BGR order).
+    ///
+    /// Images in other formats are copied unchanged.
+    pub fn to_interleaved(&self) -> OwnedImage<T> {
+        let format = match self.xi_img.frm {
+            XI_IMG_FORMAT::XI_RGB_PLANAR => XI_IMG_FORMAT::XI_RGB24,
+            XI_IMG_FORMAT::XI_RGB16_PLANAR => XI_IMG_FORMAT::XI_RGB48,
+            _ => return self.to_owned_image(),
+        };
+        let (width, height) = (self.xi_img.width as usize, self.xi_img.height as usize);
+        let mut data = Vec::with_capacity(width * height * 3);
+        for y in 0..height {
+            for x in 0..width {
+                // Planes are stored as R, G, B
+                data.extend((0..3).rev().filter_map(|channel| self.sample(x, y, channel)).cloned());
+            }
+        }
+        let mut xi_img = self.xi_img;
+        xi_img.bp = std::ptr::null_mut();
+        xi_img.frm = format;
+        xi_img.padding_x = 0;
+        xi_img.bp_size = (data.len() * size_of::<T>()) as u32;
+        OwnedImage { xi_img, data }
+    }
+}
+
+/// An image that owns a copy of its pixel data.
+///
+/// Unlike [Image], an OwnedImage is not tied to the lifetime of the acquisition buffer.
+/// Use [OwnedImage::image] to access the pixels and metadata with the same interface as for
+/// images that come directly from the camera.
+#[derive(Clone)]
+pub struct OwnedImage<T> {
+    pub(crate) xi_img: XI_IMG,
+    pub(crate) data: Vec<T>,
+}
+
+impl<T> OwnedImage<T> {
+    /// Create an image from pixel data without padding, e.g. for offline processing.
+    ///
+    /// Returns `None` if the length of `data` does not match the size and format of the image.
+    pub fn new(
+        width: u32,
+        height: u32,
+        format: xiapi_sys::XI_IMG_FORMAT::Type,
+        data: Vec<T>,
+    ) -> Option<Self> {
+        let mut xi_img = XI_IMG {
+            size: size_of::<XI_IMG>() as u32,
+            frm: format,
+            width,
+            height,
+            ..Default::default()
+        };
+        xi_img.bp_size = (data.len() * size_of::<T>()) as u32;
+        let image = OwnedImage { xi_img, data };
+        let nb_channels = image.image().nb_channels();
+        match width as usize * height as usize * nb_channels == image.data.len() {
+            true => Some(image),
+            false => None,
+        }
+    }
+
+    /// Get a view of this image with the same interface as images from the camera.
+    pub fn image(&self) -> Image<'_, T> {
+        let mut xi_img = self.xi_img;
+        xi_img.bp = self.data.as_ptr() as *mut std::os::raw::c_void;
+        Image {
+            xi_img,
+            pix_type: std::marker::PhantomData,
+        }
+    }
+
+    /// Get a mutable line of the image without the padding at its end.
+    pub(crate) fn row_mut(&mut self, y: usize) -> Option<&mut [T]> {
+        let view = self.image();
+        let start = view.stride() / size_of::<T>() * y;
+        let length = view.width() as usize * view.nb_channels();
+        if y >= view.height() as usize {
+            return None;
+        }
+        self.data.get_mut(start..start + length)
+    }
+
+    /// Mutable access to the raw image data (including padding).
+    pub fn data_mut(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Consume the image and return the raw image data (including padding).
+    pub fn into_data(self) -> Vec<T> {
+        self.data
+    }
+}
+
+// The data pointer is only set in views created by [OwnedImage::image]
+unsafe impl<T: Send> Send for OwnedImage<T> {}
+unsafe impl<T: Sync> Sync for OwnedImage<T> {}
+
 #[cfg(feature = "image")]
 impl<P> From<Image<'_, P::Subpixel>> for ImageBuffer<P, Vec<P::Subpixel>>
 where
@@ -177,7 +486,6 @@ where
     /// # Ok(())
     /// # }
     /// ```
-
     fn from(image: Image<P::Subpixel>) -> Self {
         let data = Vec::from(image.data());
         match Self::from_raw(image.width(), image.height(), data) {
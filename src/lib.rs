@@ -10,18 +10,101 @@
 
 #![warn(missing_docs)]
 
+pub use self::bracketing::ExposureBracket;
+pub use self::bracketing::HdrImage;
+pub use self::calibration::CalibrationFrame;
+pub use self::calibration::CalibrationLimits;
+pub use self::calibration::FlatFieldCorrection;
+pub use self::camera::device_name;
 pub use self::camera::number_devices;
 pub use self::camera::open_device;
+pub use self::camera::open_device_by_serial;
 pub use self::camera::open_device_manual_bandwidth;
 pub use self::camera::AcquisitionBuffer;
 pub use self::camera::Camera;
+pub use self::counters::CounterDelta;
+pub use self::counters::Counters;
+pub use self::defects::Defect;
+pub use self::defects::DefectDetector;
+pub use self::defects::DefectKind;
+pub use self::defects::DefectList;
+pub use self::dng::DngInfo;
+pub use self::ffs::FfsFile;
+pub use self::ffs::FfsReader;
+pub use self::ffs::FfsWriter;
+pub use self::focus::FocusMetric;
+pub use self::focus::FocusSweep;
+pub use self::focus::FocusSweepConfig;
+pub use self::gpio::gpo_mode_with_polarity;
+pub use self::gpio::Edge;
+pub use self::gpio::EdgeDetector;
+pub use self::gpio::Gpi;
+pub use self::gpio::GpiEdge;
+pub use self::gpio::Gpo;
+pub use self::gpio::GpoPolarity;
+pub use self::gpio::OutputConfig;
+pub use self::gpio::TriggerConfig;
+pub use self::group::CameraGroup;
+pub use self::group::FrameSet;
+pub use self::group::GroupAcquisition;
+pub use self::group::SyncConfig;
+pub use self::hdr::HdrConfig;
+pub use self::hdr::HdrKneepoint;
+pub use self::hotplug::device_serial_number;
+pub use self::hotplug::device_serial_numbers;
+pub use self::hotplug::DeviceEvent;
+pub use self::hotplug::DeviceEvents;
+pub use self::hotplug::DeviceWatcher;
+pub use self::hotplug::SupervisedCamera;
 pub use self::image::Image;
+pub use self::image::OwnedImage;
+pub use self::image::PixelValue;
+pub use self::lut::Lut;
+pub use self::named::ParamValue;
+pub use self::packing::packed_size;
+pub use self::packing::unpack;
+pub use self::playback::SequenceReader;
+pub use self::recorder::Recorder;
+pub use self::recorder::RecorderConfig;
+pub use self::recorder::RecorderStats;
+pub use self::recovery::ErrorClass;
+pub use self::recovery::RecoveryPoint;
 pub use self::roi::Roi;
+pub use self::selector::SelectorGuard;
+pub use self::ser::SerInfo;
+pub use self::ser::SerWriter;
+pub use self::sequence::FrameMetadata;
+pub use self::statistics::ChannelStatistics;
+pub use self::statistics::Histogram;
+pub use self::statistics::StatisticsConfig;
+pub use self::tiff::TiffInfo;
 pub use xiapi_sys::*;
 
+mod bracketing;
+mod calibration;
 mod camera;
+mod counters;
+mod defects;
+mod dng;
+mod ffs;
+mod focus;
+mod gpio;
+mod group;
+mod hdr;
+mod hotplug;
 mod image;
+mod lut;
+mod named;
+mod packing;
+mod playback;
+mod recorder;
+mod recovery;
 mod roi;
+mod selector;
+mod ser;
+mod sequence;
+mod statistics;
+mod tiff;
 
 /// Set the debug output level for the whole application
 pub fn set_debug_level(level: XI_DEBUG_LEVEL::Type) -> Result<(), XI_RETURN> {
@@ -41,6 +124,7 @@ pub fn set_debug_level(level: XI_DEBUG_LEVEL::Type) -> Result<(), XI_RETURN> {
 }
 
 #[cfg(test)]
+#[allow(clippy::single_match)]
 mod tests {
     use std::os::raw::c_char;
     use crate::*;
@@ -265,6 +349,1018 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[serial]
+    fn set_get_hdr_config() -> Result<(), XI_RETURN> {
+        let mut cam = open_device(None)?;
+        let config = HdrConfig {
+            kneepoint1: HdrKneepoint { time: 60, level: 40 },
+            kneepoint2: Some(HdrKneepoint { time: 90, level: 80 }),
+        };
+        if let Err(x) = cam.set_hdr_config(&config) {
+            return match x as XI_RET::Type {
+                XI_RET::XI_NOT_SUPPORTED => Ok(()), // Camera has no sensor HDR
+                _ => Err(x),
+            };
+        }
+        assert_eq!(cam.hdr_config()?, Some(config));
+        cam.disable_hdr()?;
+        assert_eq!(cam.hdr_config()?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn hdr_linearize() {
+        let config = HdrConfig {
+            kneepoint1: HdrKneepoint { time: 80, level: 40 },
+            kneepoint2: None,
+        };
+        assert_eq!(config.validate(), Ok(()));
+        // Below the kneepoint the response is linear
+        assert_abs_diff_eq!(config.linearize(250.0, 1000.0), 250.0, epsilon = 1e-3);
+        // Above the kneepoint pixels only integrate during the last 20% of the exposure
+        assert_abs_diff_eq!(config.linearize(600.0, 1000.0), 1000.0, epsilon = 1e-3);
+
+        let invalid = HdrConfig {
+            kneepoint1: HdrKneepoint { time: 80, level: 40 },
+            kneepoint2: Some(HdrKneepoint { time: 70, level: 60 }),
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn merge_exposure_bracket() -> Result<(), XI_RETURN> {
+        let mut short = OwnedImage::new(3, 1, XI_MONO8, vec![10u8, 100, 255]).unwrap();
+        short.xi_img.exposure_time_us = 1000;
+        let mut long = OwnedImage::new(3, 1, XI_MONO8, vec![40u8, 255, 255]).unwrap();
+        long.xi_img.exposure_time_us = 4000;
+        let hdr = HdrImage::merge(&[short, long])?;
+        assert_abs_diff_eq!(hdr.pixel(0, 0).unwrap()[0], 0.01, epsilon = 1e-6);
+        // Saturated pixels in the long exposure are ignored
+        assert_abs_diff_eq!(hdr.pixel(1, 0).unwrap()[0], 0.1, epsilon = 1e-6);
+        // Pixels saturated in all frames use the shortest exposure
+        assert_abs_diff_eq!(hdr.pixel(2, 0).unwrap()[0], 0.255, epsilon = 1e-6);
+        let tone_mapped = hdr.tone_map(0.18)?;
+        assert_eq!(tone_mapped.image().data().len(), 3);
+        // Formats without pixel channels can not be merged
+        let mut unknown = OwnedImage::new(1, 1, XI_MONO8, vec![0u8]).unwrap();
+        unknown.xi_img.exposure_time_us = 1000;
+        unknown.xi_img.frm = u32::MAX;
+        assert_eq!(
+            HdrImage::merge(&[unknown]).err(),
+            Some(XI_RET::XI_NOT_SUPPORTED_DATA_FORMAT as XI_RETURN)
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn capture_exposure_bracket() -> Result<(), XI_RETURN> {
+        let cam = open_device(None)?;
+        let mut acq = cam.start_acquisition()?;
+        let bracket = ExposureBracket::geometric(1000.0, 2.0, 3);
+        let frames = acq.capture_bracket::<u8>(&bracket, None)?;
+        assert_eq!(frames.len(), 3);
+        for (frame, exposure) in frames.iter().zip(&bracket.exposures) {
+            assert_abs_diff_eq!(frame.image().exposure_time_us() as f32, exposure, epsilon = 20.0);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn lut_curves() {
+        let gamma = Lut::gamma(256, 255, 2.0);
+        assert_eq!(gamma.len(), 256);
+        assert_eq!(gamma[64], 128);
+        assert_eq!(*Lut::inverted(4, 3), [3, 2, 1, 0]);
+        assert_eq!(*Lut::threshold(4, 9, 2), [0, 0, 9, 9]);
+        assert_eq!(*Lut::clip(5, 1, 3), [1, 1, 2, 3, 3]);
+    }
+
+    #[test]
+    #[serial]
+    fn upload_read_lut() -> Result<(), XI_RETURN> {
+        let mut cam = open_device(None)?;
+        let lut = Lut::inverted(cam.lut_len()?, cam.lut_value_maximum()?);
+        cam.set_lut(&lut)?;
+        cam.set_lut_en(XI_SWITCH::XI_ON)?;
+        assert_eq!(cam.lut()?, lut);
+        assert_eq!(cam.set_lut(&lut[1..]), Err(XI_RET::XI_WRONG_PARAM_SIZE as XI_RETURN));
+        cam.set_lut_en(XI_SWITCH::XI_OFF)?;
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn load_user_set() -> Result<(), XI_RETURN> {
+        let mut cam = open_device(None)?;
+        let default_set = cam.user_set_default()?;
+        cam.load_user_set(default_set)?;
+        assert_eq!(cam.user_set_selector()?, default_set);
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn ffs_write_read_truncate() -> Result<(), XI_RETURN> {
+        use std::io::{Read, Write};
+        let mut cam = open_device(None)?;
+        let free_before = cam.ffs_free_size()?;
+        {
+            let mut writer = cam.ffs_writer("xiapi_test.bin");
+            writer.write_all(&[42u8; 16]).unwrap();
+            writer.finish()?;
+        }
+        assert!(cam.ffs_files()?.iter().any(|file| file.name == "xiapi_test.bin"));
+        let mut data = Vec::new();
+        cam.ffs_reader("xiapi_test.bin")?.read_to_end(&mut data).unwrap();
+        assert_eq!(data, vec![42u8; 16]);
+        let too_large = vec![0u8; free_before + 1024];
+        assert!(cam.ffs_write("xiapi_test_large.bin", &too_large).is_err());
+        cam.ffs_truncate("xiapi_test.bin")?;
+        let file = cam.ffs_files()?.into_iter().find(|file| file.name == "xiapi_test.bin");
+        assert_eq!(file.map(|file| file.size), Some(0));
+        Ok(())
+    }
+
+    #[test]
+    fn flat_field_correction() -> Result<(), XI_RETURN> {
+        let frame = |data: Vec<u8>| {
+            let image = OwnedImage::new(4, 1, XI_MONO8, data).unwrap();
+            CalibrationFrame::from(&image.image())
+        };
+        let limits = CalibrationLimits::default();
+        let dark = frame(vec![5, 5, 5, 5]);
+        let flat = frame(vec![105, 205, 105, 205]);
+        let correction = FlatFieldCorrection::new(dark.clone(), flat, &limits)?;
+        let raw = OwnedImage::new(4, 1, XI_MONO8, vec![55u8, 105, 105, 205]).unwrap();
+        let corrected = correction.apply(&raw.image())?;
+        assert_eq!(corrected.image().data(), [75, 75, 150, 150]);
+
+        let too_dark = frame(vec![10, 10, 10, 10]);
+        assert_eq!(
+            FlatFieldCorrection::new(dark.clone(), too_dark, &limits),
+            Err(XI_RET::XI_SHADING_TOODARK as XI_RETURN)
+        );
+        let uneven = frame(vec![20, 200, 200, 200]);
+        assert_eq!(
+            FlatFieldCorrection::new(dark, uneven, &limits),
+            Err(XI_RET::XI_SHADING_TOOCONTRAST as XI_RETURN)
+        );
+
+        // A single dead pixel and the different levels of the colors are accepted
+        let mut data = vec![200u8; 1000];
+        data[500] = 0;
+        let image = OwnedImage::new(1000, 1, XI_MONO8, data).unwrap();
+        assert_eq!(CalibrationFrame::from(&image.image()).validate_flat(&limits), Ok(()));
+        let data = [60u8, 200, 100].repeat(4);
+        let image = OwnedImage::new(4, 1, XI_IMG_FORMAT::XI_RGB24, data).unwrap();
+        assert_eq!(CalibrationFrame::from(&image.image()).validate_flat(&limits), Ok(()));
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn capture_calibration_frame() -> Result<(), XI_RETURN> {
+        let cam = open_device(None)?;
+        let roi = cam.roi()?;
+        let acq = cam.start_acquisition()?;
+        let average = acq.capture_average::<u8>(4, None)?;
+        assert_eq!(average.width(), roi.width);
+        assert_eq!(average.height(), roi.height);
+        assert_eq!(acq.capture_average::<u8>(0, None), Err(XI_RET::XI_INVALID_ARG as XI_RETURN));
+        Ok(())
+    }
+
+    #[test]
+    fn detect_and_correct_defects() -> Result<(), XI_RETURN> {
+        let frame = |data: Vec<u8>| {
+            let image = OwnedImage::new(4, 3, XI_MONO8, data).unwrap();
+            CalibrationFrame::from(&image.image())
+        };
+        let mut dark = vec![5u8; 12];
+        dark[5] = 60;
+        dark[8] = 200;
+        let mut flat = vec![150u8; 12];
+        flat[5] = 250;
+        flat[6] = 6;
+        flat[8] = 200;
+        let defects = DefectDetector::default().detect(&frame(dark), &frame(flat))?;
+        let found: Vec<_> = defects.iter().map(|d| (d.x, d.y, d.kind)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, 1, DefectKind::Hot),
+                (2, 1, DefectKind::Dead),
+                (0, 2, DefectKind::Stuck)
+            ]
+        );
+
+        let list = DefectList::parse("# user list\n1 1\n2,1\n")?;
+        assert_eq!(list.to_list_content(), "1 1\n2 1\n");
+        assert!(DefectList::parse("1").is_err());
+        let mut data = vec![100u8; 12];
+        data[5] = 255;
+        data[6] = 0;
+        let image = OwnedImage::new(4, 3, XI_MONO8, data).unwrap();
+        let corrected = list.correct(&image.image());
+        assert_eq!(corrected.image().data(), [100u8; 12]);
+        Ok(())
+    }
+
+    #[test]
+    fn detect_defects_per_bayer_position() -> Result<(), XI_RETURN> {
+        let frame = |data: Vec<u8>| {
+            let mut image = OwnedImage::new(4, 2, XI_IMG_FORMAT::XI_RAW8, data).unwrap();
+            image.xi_img.color_filter_array = XI_COLOR_FILTER_ARRAY::XI_CFA_BAYER_RGGB;
+            CalibrationFrame::from(&image.image())
+        };
+        // Red and blue respond much less than green
+        let dark = vec![5u8; 8];
+        let mut flat = vec![40u8, 150, 40, 150, 150, 40, 150, 40];
+        flat[6] = 30;
+        let defects = DefectDetector::default().detect(&frame(dark), &frame(flat))?;
+        let found: Vec<_> = defects.iter().map(|d| (d.x, d.y, d.kind)).collect();
+        assert_eq!(found, vec![(2, 1, DefectKind::Dead)]);
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn upload_defect_list() -> Result<(), XI_RETURN> {
+        use XI_SENS_DEFFECTS_CORR_LIST_SELECTOR::XI_SENS_DEFFECTS_CORR_LIST_SEL_USER0;
+        let mut cam = open_device(None)?;
+        let list = DefectList::parse("10 20\n30 40\n")?;
+        cam.set_defect_list(XI_SENS_DEFFECTS_CORR_LIST_SEL_USER0, &list)?;
+        let read_back = cam.defect_list(XI_SENS_DEFFECTS_CORR_LIST_SEL_USER0)?;
+        assert!(read_back.contains(10, 20) && read_back.contains(30, 40));
+        cam.set_bpc(XI_SWITCH::XI_ON)?;
+        Ok(())
+    }
+
+    #[test]
+    fn match_group_frames() -> Result<(), XI_RETURN> {
+        use crate::group::find_partner;
+        let frame = |number: u32| {
+            let mut image = OwnedImage::new(1, 1, XI_MONO8, vec![0u8]).unwrap();
+            image.xi_img.acq_nframe = number;
+            image
+        };
+        let mut stream = vec![frame(1), frame(2), frame(4)].into_iter();
+        let mut next_frame = || stream.next().ok_or(XI_RET::XI_TIMEOUT as XI_RETURN);
+        let mut pending = None;
+        let matched = find_partner(&mut pending, 2, &mut next_frame)?;
+        assert_eq!(matched.map(|f| f.image().acq_nframe()), Some(2));
+        // Frame 3 was lost, frame 4 is kept for the next set
+        assert!(find_partner(&mut pending, 3, &mut next_frame)?.is_none());
+        let matched = find_partner(&mut pending, 4, &mut next_frame)?;
+        assert_eq!(matched.map(|f| f.image().acq_nframe()), Some(4));
+        assert!(find_partner(&mut pending, 5, &mut next_frame)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn realign_group_frames() -> Result<(), XI_RETURN> {
+        use crate::group::{MasterTiming, SlaveSync};
+        let frame = |number: u32, timestamp: u32| {
+            let mut image = OwnedImage::new(1, 1, XI_MONO8, vec![0u8]).unwrap();
+            image.xi_img.acq_nframe = number;
+            image.xi_img.tsUSec = timestamp;
+            image
+        };
+        let timing = |timestamp| {
+            Some(MasterTiming {
+                timestamp,
+                period: 1000.0,
+                tolerance: 0.5,
+            })
+        };
+        // The slave missed the third trigger, so its frame 3 belongs to master frame 4
+        let mut stream = vec![frame(1, 100), frame(2, 1100), frame(3, 3100), frame(4, 4100)].into_iter();
+        let mut next_frame = || stream.next().ok_or(XI_RET::XI_TIMEOUT as XI_RETURN);
+        let mut sync = SlaveSync::default();
+        let mut next_partner = |number: u32, timestamp: u64, timing| {
+            let (frame, realigned) = sync.next_partner(number, timestamp, timing, &mut next_frame)?;
+            Ok::<_, XI_RETURN>((frame.map(|f: OwnedImage<u8>| f.image().acq_nframe()), realigned))
+        };
+        assert_eq!(next_partner(1, 0, None)?, (Some(1), false));
+        assert_eq!(next_partner(2, 1000, timing(1000))?, (Some(2), false));
+        assert_eq!(next_partner(3, 2000, timing(2000))?, (None, true));
+        assert_eq!(next_partner(4, 3000, timing(3000))?, (Some(3), false));
+        assert_eq!(next_partner(5, 4000, timing(4000))?, (Some(4), false));
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn group_acquisition() -> Result<(), XI_RETURN> {
+        let master = open_device(Some(0))?;
+        let slave = open_device(Some(1))?;
+        let group = CameraGroup::new(master, vec![slave], SyncConfig::default())?;
+        let mut acq = group.start_acquisition::<u8>()?;
+        let frames = acq.next_frames(None)?;
+        assert_eq!(frames.slaves().len(), 1);
+        assert!(frames.is_complete());
+        acq.stop_acquisition()?;
+        Ok(())
+    }
+
+    #[test]
+    fn gpo_polarity() {
+        use XI_GPO_MODE::*;
+        assert_eq!(
+            gpo_mode_with_polarity(XI_GPO_EXPOSURE_ACTIVE, GpoPolarity::ActiveLow),
+            Ok(XI_GPO_EXPOSURE_ACTIVE_NEG)
+        );
+        assert_eq!(
+            gpo_mode_with_polarity(XI_GPO_BUSY_NEG, GpoPolarity::ActiveHigh),
+            Ok(XI_GPO_BUSY)
+        );
+        assert!(gpo_mode_with_polarity(XI_GPO_HIGH_IMPEDANCE, GpoPolarity::ActiveLow).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn set_trigger_config() -> Result<(), XI_RETURN> {
+        let mut cam = open_device(None)?;
+        let prev_selector = cam.gpi_selector()?;
+        let mut config = TriggerConfig::hardware(XI_GPI_SELECTOR::XI_GPI_PORT1);
+        config.outputs.push(OutputConfig {
+            port: XI_GPO_SELECTOR::XI_GPO_PORT1,
+            mode: XI_GPO_MODE::XI_GPO_EXPOSURE_ACTIVE,
+            polarity: GpoPolarity::ActiveLow,
+        });
+        cam.set_trigger_config(&config)?;
+        assert_eq!(cam.trg_source()?, XI_TRG_SOURCE::XI_TRG_EDGE_RISING);
+        assert_eq!(cam.gpo(XI_GPO_SELECTOR::XI_GPO_PORT1).polarity()?, GpoPolarity::ActiveLow);
+        assert_eq!(cam.gpi_selector()?, prev_selector);
+        cam.set_trigger_config(&TriggerConfig::software())?;
+        Ok(())
+    }
+
+    #[test]
+    fn detect_gpi_edges() {
+        let mut detector = EdgeDetector::new(&[1, 2]);
+        assert!(detector.update(&[true, false]).is_empty());
+        assert!(detector.update(&[true, false]).is_empty());
+        assert_eq!(
+            detector.update(&[false, true]),
+            vec![
+                GpiEdge { port: 1, edge: Edge::Falling },
+                GpiEdge { port: 2, edge: Edge::Rising }
+            ]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn drive_gpo_during_acq() -> Result<(), XI_RETURN> {
+        let cam = open_device(None)?;
+        let mut acq = cam.start_acquisition()?;
+        acq.set_gpo_level(XI_GPO_SELECTOR::XI_GPO_PORT1, true)?;
+        acq.gpo_pulse(XI_GPO_SELECTOR::XI_GPO_PORT1, std::time::Duration::from_millis(10))?;
+        let mut detector = EdgeDetector::new(&[XI_GPI_SELECTOR::XI_GPI_PORT1]);
+        assert!(acq.poll_gpi_edges(&mut detector)?.is_empty());
+        acq.gpi_level_of(XI_GPI_SELECTOR::XI_GPI_PORT1)?;
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn selector_restored_on_error() -> Result<(), XI_RETURN> {
+        use XI_GAIN_SELECTOR_TYPE::*;
+        let mut cam = open_device(None)?;
+        cam.set_gain_selector(XI_GAIN_SELECTOR_ALL)?;
+        let result: Result<(), XI_RETURN> =
+            cam.with_selector(XI_PRM_GAIN_SELECTOR, XI_GAIN_SELECTOR_ANALOG_ALL, |cam| {
+                assert_eq!(cam.gain_selector()?, XI_GAIN_SELECTOR_ANALOG_ALL);
+                Err(XI_RET::XI_WRONG_PARAM_VALUE as XI_RETURN)
+            });
+        assert_eq!(result, Err(XI_RET::XI_WRONG_PARAM_VALUE as XI_RETURN));
+        assert_eq!(cam.gain_selector()?, XI_GAIN_SELECTOR_ALL);
+        {
+            let _guard = cam.select(XI_PRM_GAIN_SELECTOR, XI_GAIN_SELECTOR_ANALOG_ALL)?;
+        }
+        assert_eq!(cam.gain_selector()?, XI_GAIN_SELECTOR_ALL);
+        cam.temperature(XI_TEMP_SELECTOR::XI_TEMP_SENSOR_BOARD)?;
+        Ok(())
+    }
+
+    #[test]
+    fn counter_delta() {
+        let start = Counters {
+            captured_at: std::time::Instant::now(),
+            transport_skipped_frames: Some(2),
+            api_skipped_frames: Some(1),
+            transferred_frames: Some(100),
+            missed_triggers_overlap: None,
+            missed_triggers_buffer_overflow: Some(0),
+            frame_buffer_overflows: Some(0),
+        };
+        let end = Counters {
+            captured_at: start.captured_at + std::time::Duration::from_secs(2),
+            transport_skipped_frames: Some(5),
+            api_skipped_frames: Some(1),
+            transferred_frames: Some(160),
+            ..start
+        };
+        let delta = end.since(&start);
+        assert_eq!(delta.transferred_frames, Some(60));
+        assert_eq!(delta.missed_triggers_overlap, None);
+        assert_eq!(delta.lost_frames(), Some(3));
+        assert_eq!(delta.per_second(delta.transferred_frames), Some(30.0));
+    }
+
+    #[test]
+    #[serial]
+    fn read_counters_during_acq() -> Result<(), XI_RETURN> {
+        let cam = open_device(None)?;
+        let mut acq = cam.start_acquisition()?;
+        let start = acq.counters()?;
+        for _ in 0..5 {
+            acq.next_image::<u8>(None)?;
+        }
+        let delta = acq.counters()?.since(&start);
+        assert!(delta.transferred_frames.unwrap_or(5) >= 5);
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn runtime_params_during_acq() -> Result<(), XI_RETURN> {
+        let mut cam = open_device(None)?;
+        cam.set_acq_timing_mode(XI_ACQ_TIMING_MODE::XI_ACQ_TIMING_MODE_FRAME_RATE)?;
+        let mut acq = cam.start_acquisition()?;
+        let framerate = acq.framerate_minimum()?;
+        acq.set_framerate(framerate)?;
+        assert_abs_diff_eq!(acq.framerate()?, framerate, epsilon = 0.1);
+        acq.set_image_user_data(42)?;
+        let image = acq.next_image::<u8>(None)?;
+        assert_eq!(acq.width()?, image.width());
+        Ok(())
+    }
+
+    #[test]
+    fn classify_errors() {
+        let class = |err: XI_RET::Type| ErrorClass::of(err as XI_RETURN);
+        assert_eq!(class(XI_RET::XI_TIMEOUT), ErrorClass::Transient);
+        assert_eq!(class(XI_RET::XI_ACQUISITION_STOPED), ErrorClass::RestartAcquisition);
+        assert_eq!(class(XI_RET::XI_INVALID_HANDLE), ErrorClass::DeviceLost);
+        assert_eq!(class(XI_RET::XI_WRONG_PARAM_VALUE), ErrorClass::Other);
+    }
+
+    #[test]
+    #[serial]
+    fn recover_acquisition() -> Result<(), XI_RETURN> {
+        let mut cam = open_device(None)?;
+        cam.set_exposure(2000.0)?;
+        let recovery_point = cam.recovery_point()?;
+        let mut acq = cam.start_acquisition()?;
+        assert!(acq.is_acquiring()?);
+        acq.recover(XI_RET::XI_ACQUISITION_STOPED as XI_RETURN, &recovery_point)?;
+        acq.next_image::<u8>(None)?;
+        acq.recover(XI_RET::XI_INVALID_HANDLE as XI_RETURN, &recovery_point)?;
+        assert_abs_diff_eq!(acq.exposure()?, 2000.0, epsilon = 100.0);
+        acq.next_image::<u8>(None)?;
+        Ok(())
+    }
+
+    #[test]
+    fn device_watcher_events() {
+        let serials = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let mut watcher = DeviceWatcher::default();
+        assert_eq!(
+            watcher.update(serials(&["A", "B"])),
+            vec![
+                DeviceEvent::Arrived("A".to_string()),
+                DeviceEvent::Arrived("B".to_string())
+            ]
+        );
+        assert!(watcher.update(serials(&["B", "A"])).is_empty());
+        assert_eq!(
+            watcher.update(serials(&["B", "C"])),
+            vec![
+                DeviceEvent::Removed("A".to_string()),
+                DeviceEvent::Arrived("C".to_string())
+            ]
+        );
+        assert_eq!(watcher.devices().collect::<Vec<_>>(), vec!["B", "C"]);
+    }
+
+    #[test]
+    fn stop_device_watcher() {
+        use std::time::{Duration, Instant};
+        let start = Instant::now();
+        let events = DeviceWatcher::default().watch(Duration::from_secs(3600));
+        drop(events);
+        assert!(start.elapsed() < Duration::from_secs(60));
+    }
+
+    #[test]
+    #[serial]
+    fn supervised_camera() -> Result<(), XI_RETURN> {
+        let cam = open_device(None)?;
+        let serial_number = cam.serial_number()?;
+        assert!(device_serial_numbers()?.contains(&serial_number));
+        let mut supervised = SupervisedCamera::start(cam, std::time::Duration::from_secs(5))?;
+        supervised.next_image::<u8>(None)?;
+        supervised.save_configuration()?;
+        assert_eq!(supervised.reconnects(), 0);
+        supervised.stop()?;
+        Ok(())
+    }
+
+    #[test]
+    fn parse_param_values() {
+        assert_eq!("42".parse(), Ok(ParamValue::Int(42)));
+        assert_eq!(" -1 ".parse(), Ok(ParamValue::Int(-1)));
+        assert_eq!("2.5".parse(), Ok(ParamValue::Float(2.5)));
+        assert_eq!("XI_MONO8".parse(), Ok(ParamValue::String("XI_MONO8".to_string())));
+        assert_eq!(ParamValue::Float(2.5).to_string(), "2.5");
+    }
+
+    #[test]
+    #[serial]
+    fn param_by_name() -> Result<(), XI_RETURN> {
+        let mut cam = open_device(None)?;
+        cam.set_param_by_name("exposure", &ParamValue::Int(5000))?;
+        assert_eq!(cam.param_by_name("exposure")?, ParamValue::Int(5000));
+        assert_eq!(cam.int_param_by_name("width")?, cam.width()? as i32);
+        Ok(())
+    }
+
+    #[test]
+    fn record_sequence() -> std::io::Result<()> {
+        let path = std::env::temp_dir().join("xiapi_record_sequence.xiseq");
+        let config = RecorderConfig {
+            pool_size: 200,
+            writer_threads: 3,
+        };
+        let mut recorder = Recorder::create(&path, 12, config)?;
+        for nframe in 0..100u8 {
+            let mut frame = OwnedImage::new(4, 3, XI_IMG_FORMAT::XI_MONO8, vec![nframe; 12]).unwrap();
+            frame.xi_img.nframe = nframe as u32;
+            assert!(recorder.record(&frame.image())?);
+        }
+        let stats = recorder.finish()?;
+        assert_eq!(stats.recorded_frames, 100);
+        assert_eq!(stats.dropped_frames, 0);
+        let data = std::fs::read(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(data.len() as u64, stats.bytes_written);
+        assert_eq!(&data[..5], b"XISEQ");
+        let record_size = 72 + 12;
+        assert_eq!(data.len(), 16 + 100 * record_size);
+        for nframe in 0..100 {
+            let record = &data[16 + nframe * record_size..][..record_size];
+            assert_eq!(record[8], nframe as u8);
+            assert!(record[72..].iter().all(|&value| value == nframe as u8));
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn record_from_acquisition() -> Result<(), XI_RETURN> {
+        let cam = open_device(None)?;
+        let acq = cam.start_acquisition()?;
+        let path = std::env::temp_dir().join("xiapi_record_acquisition.xiseq");
+        let mut recorder = Recorder::for_acquisition(&path, &acq, Default::default()).unwrap();
+        recorder.record_from::<u8>(&acq, 10, None).unwrap();
+        let stats = recorder.finish().unwrap();
+        assert_eq!(stats.recorded_frames + stats.dropped_frames, 10);
+        std::fs::remove_file(&path).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn play_back_sequence() -> std::io::Result<()> {
+        let path = std::env::temp_dir().join("xiapi_play_back_sequence.xiseq");
+        let mut recorder = Recorder::create(&path, 24, Default::default())?;
+        for nframe in 0..10u16 {
+            let data = (0..12).map(|i| nframe * 1000 + i).collect();
+            let mut frame = OwnedImage::new(4, 3, XI_IMG_FORMAT::XI_MONO16, data).unwrap();
+            frame.xi_img.nframe = nframe as u32;
+            frame.xi_img.tsUSec = nframe as u32 * 100;
+            frame.xi_img.exposure_time_us = 500;
+            while !recorder.record(&frame.image())? {}
+        }
+        recorder.finish()?;
+        let mut reader = SequenceReader::open(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(reader.len(), 10);
+        assert_eq!(reader.metadata(3).unwrap().timestamp_raw, 300);
+        let frame = reader.read_frame::<u16>(7)?;
+        assert_eq!(frame.image().nframe(), 7);
+        assert_eq!(frame.image().exposure_time_us(), 500);
+        assert_eq!(frame.image().pixel(1, 2), Some(&7009));
+        reader.set_range(8..20);
+        assert_eq!(reader.range(), 8..10);
+        let frames: Vec<u32> = std::iter::from_fn(|| reader.next_image::<u16>().unwrap())
+            .map(|frame| frame.image().nframe())
+            .collect();
+        assert_eq!(frames, vec![8, 9]);
+        reader.seek(0);
+        assert_eq!(reader.position(), 8);
+        Ok(())
+    }
+
+    #[test]
+    fn tiff_round_trip() -> std::io::Result<()> {
+        let data: Vec<u16> = (0..12).map(|i| i * 300).collect();
+        let mut raw = OwnedImage::new(4, 3, XI_IMG_FORMAT::XI_RAW16, data.clone()).unwrap();
+        raw.xi_img.color_filter_array = XI_COLOR_FILTER_ARRAY::XI_CFA_BAYER_GRBG;
+        raw.xi_img.exposure_time_us = 2500;
+        raw.xi_img.gain_db = 6.5;
+        raw.xi_img.tsSec = 1;
+        raw.xi_img.tsUSec = 42;
+        raw.xi_img.nframe = 17;
+        let info = TiffInfo {
+            significant_bits: Some(12),
+            serial_number: Some("12345678".to_string()),
+            model: Some("MC050MG-SY".to_string()),
+        };
+        let mut file = std::io::Cursor::new(Vec::new());
+        raw.image().write_tiff(&mut file, &info)?;
+        file.set_position(0);
+        let (read, read_info) = OwnedImage::<u16>::read_tiff(&mut file)?;
+        assert_eq!(read_info, info);
+        let image = read.image();
+        assert_eq!(image.format(), XI_IMG_FORMAT::XI_RAW16);
+        assert_eq!(image.color_filter_array(), XI_COLOR_FILTER_ARRAY::XI_CFA_BAYER_GRBG);
+        assert_eq!(image.exposure_time_us(), 2500);
+        assert_eq!(image.gain_db(), 6.5);
+        assert_eq!(image.timestamp_raw(), (1 << 32) + 42);
+        assert_eq!(image.nframe(), 17);
+        assert_eq!(image.data(), &data[..]);
+        assert!(OwnedImage::<u8>::read_tiff(&mut std::io::Cursor::new(file.into_inner())).is_err());
+
+        // RGB with padding is stored as RGB without padding
+        let mut bgr = OwnedImage::new(2, 1, XI_IMG_FORMAT::XI_RGB24, vec![1u8, 2, 3, 4, 5, 6]).unwrap();
+        bgr.data.extend([0, 0]);
+        bgr.xi_img.padding_x = 2;
+        bgr.xi_img.bp_size = 8;
+        let mut file = std::io::Cursor::new(Vec::new());
+        bgr.image().write_tiff(&mut file, &TiffInfo::default())?;
+        assert_eq!(&file.get_ref()[8..14], &[3, 2, 1, 6, 5, 4]);
+        #[cfg(feature = "image")]
+        {
+            let decoded = ::image::load_from_memory(file.get_ref()).unwrap().into_rgb8();
+            assert_eq!(decoded.into_raw(), vec![3, 2, 1, 6, 5, 4]);
+        }
+        file.set_position(0);
+        let (read, read_info) = OwnedImage::<u8>::read_tiff(&mut file)?;
+        assert_eq!(read_info, TiffInfo::default());
+        assert_eq!(read.image().format(), XI_IMG_FORMAT::XI_RGB24);
+        assert_eq!(read.image().data(), &[1, 2, 3, 4, 5, 6]);
+        Ok(())
+    }
+
+    #[test]
+    fn write_dng() -> std::io::Result<()> {
+        let data: Vec<u16> = (0..16).map(|i| 64 + i * 200).collect();
+        let mut raw = OwnedImage::new(4, 4, XI_IMG_FORMAT::XI_RAW16, data.clone()).unwrap();
+        raw.xi_img.color_filter_array = XI_COLOR_FILTER_ARRAY::XI_CFA_BAYER_RGGB;
+        raw.xi_img.black_level = 64;
+        let info = DngInfo {
+            model: Some("MC050CG-SY".to_string()),
+            serial_number: Some("12345678".to_string()),
+            bit_depth: Some(12),
+            white_balance: Some([2.0, 1.0, 1.5]),
+            color_matrix: None,
+        };
+        let mut file = std::io::Cursor::new(Vec::new());
+        raw.image().write_dng(&mut file, &info)?;
+        let bytes = file.get_ref();
+        let contains = |pattern: &[u8]| bytes.windows(pattern.len()).any(|w| w == pattern);
+        // DNGVersion 1.4 and WhiteLevel 4095 entries
+        assert!(contains(&[0x12, 0xc6, 1, 0, 4, 0, 0, 0, 1, 4, 0, 0]));
+        assert!(contains(&[0x1d, 0xc6, 4, 0, 1, 0, 0, 0, 0xff, 0x0f, 0, 0]));
+        assert!(contains(b"12345678\0"));
+        file.set_position(0);
+        let (read, read_info) = OwnedImage::<u16>::read_tiff(&mut file)?;
+        assert_eq!(read_info.model.as_deref(), Some("MC050CG-SY"));
+        assert_eq!(read.image().format(), XI_IMG_FORMAT::XI_RAW16);
+        assert_eq!(read.image().color_filter_array(), XI_COLOR_FILTER_ARRAY::XI_CFA_BAYER_RGGB);
+        assert_eq!(read.image().data(), &data[..]);
+
+        let mono = OwnedImage::new(4, 4, XI_IMG_FORMAT::XI_MONO16, data).unwrap();
+        assert!(mono.image().write_dng(&mut std::io::sink(), &info).is_err());
+
+        // White balanced camera RGB that equals sRGB
+        let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let matrix = crate::dng::dng_color_matrix(Some(identity), Some([2.0, 1.0, 1.0]));
+        assert_abs_diff_eq!(matrix[0][0], 3.240454 / 2.0, epsilon = 1e-5);
+        assert_abs_diff_eq!(matrix[1][1], 1.876011, epsilon = 1e-5);
+        Ok(())
+    }
+
+    #[test]
+    fn write_ser() -> std::io::Result<()> {
+        let info = SerInfo {
+            instrument: "XIMEA MC050CG-SY".to_string(),
+            bit_depth: Some(12),
+            timestamp_tick: Some(std::time::Duration::from_micros(1)),
+            ..Default::default()
+        };
+        let mut ser = SerWriter::new(std::io::Cursor::new(Vec::new()), info)?;
+        for nframe in 0..3u32 {
+            let data = (0..8).map(|i| i as u16 * 512 + nframe as u16).collect();
+            let mut raw = OwnedImage::new(4, 2, XI_IMG_FORMAT::XI_RAW16, data).unwrap();
+            raw.xi_img.color_filter_array = XI_COLOR_FILTER_ARRAY::XI_CFA_BAYER_GBRG;
+            raw.xi_img.tsUSec = 1000 + nframe * 20_000;
+            ser.write_frame(&raw.image())?;
+        }
+        let mono = OwnedImage::new(4, 2, XI_IMG_FORMAT::XI_MONO16, vec![0u16; 8]).unwrap();
+        assert!(ser.write_frame(&mono.image()).is_err());
+        assert_eq!(ser.frame_count(), 3);
+        let data = ser.finish()?.into_inner();
+        let i32_at = |offset: usize| i32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        assert_eq!(&data[..14], b"LUCAM-RECORDER");
+        assert_eq!(i32_at(18), 10);
+        assert_eq!((i32_at(26), i32_at(30), i32_at(34), i32_at(38)), (4, 2, 12, 3));
+        assert_eq!(&data[82..98], b"XIMEA MC050CG-SY");
+        assert_eq!(data.len(), 178 + 3 * 16 + 3 * 8);
+        // Second pixel of the second frame
+        assert_eq!(&data[178 + 16 + 2..][..2], &513u16.to_le_bytes());
+        let trailer = 178 + 3 * 16;
+        assert_eq!(u64_at(trailer), u64_at(162));
+        assert_eq!(u64_at(trailer + 16) - u64_at(trailer), 400_000);
+
+        // The alpha channel of RGB32 is dropped
+        let mut ser = SerWriter::new(std::io::Cursor::new(Vec::new()), SerInfo::default())?;
+        let bgra = OwnedImage::new(2, 1, XI_IMG_FORMAT::XI_RGB32, vec![1u8, 2, 3, 255, 4, 5, 6, 255]).unwrap();
+        ser.write_frame(&bgra.image())?;
+        let data = ser.finish()?.into_inner();
+        assert_eq!(i32::from_le_bytes(data[18..22].try_into().unwrap()), 101);
+        assert_eq!(&data[178..184], [1, 2, 3, 4, 5, 6]);
+        assert_eq!(data.len(), 178 + 6 + 8);
+        Ok(())
+    }
+
+    #[test]
+    fn unpack_packed_values() -> Result<(), XI_RETURN> {
+        use XI_OUTPUT_DATA_PACKING_TYPE::*;
+        let cases: [(&[u8], u32, _, &[u16]); 3] = [
+            (&[0xff, 0x03, 0x50, 0x95, 0xaa], 10, XI_DATA_PACK_PFNC_LSB_PACKING, &[0x3ff, 0, 0x155, 0x2aa]),
+            (&[0xbc, 0x3a, 0x12], 12, XI_DATA_PACK_PFNC_LSB_PACKING, &[0xabc, 0x123]),
+            (&[0xff, 0x3f, 0x8d, 0x04], 14, XI_DATA_PACK_PFNC_LSB_PACKING, &[0x3fff, 0x1234]),
+        ];
+        for (packed, bit_depth, packing, expected) in cases {
+            let mut values = vec![0u16; expected.len()];
+            unpack(packed, bit_depth, packing, &mut values)?;
+            assert_eq!(values, expected, "{bit_depth} bits, packing {packing}");
+        }
+        let mut values = [0u16; 5];
+        unpack(&[0xff, 0x03, 0x50, 0x95, 0xaa, 0x01, 0x02], 10, XI_DATA_PACK_PFNC_LSB_PACKING, &mut values)?;
+        assert_eq!(values, [0x3ff, 0, 0x155, 0x2aa, 0x201]);
+
+        // XIMEA grouping of 16 pixels: MSB bytes 0x00, 0x11, .., 0xff followed by the LSBs
+        let msb: Vec<u8> = (0..16).map(|i| i * 0x11).collect();
+        // 10g160: LSBs 0, 1, 2, 3, 0, 1, .. in 2 bit fields
+        let packed = [&msb[..], &[0xe4; 4]].concat();
+        let mut values = [0u16; 16];
+        unpack(&packed, 10, XI_DATA_PACK_XI_GROUPING, &mut values)?;
+        let expected: Vec<u16> = (0..16).map(|i| ((i * 0x11) << 2) | (i % 4)).collect();
+        assert_eq!(values, expected[..]);
+        // 12g192: LSBs 15, 14, .., 0 in 4 bit fields
+        let packed = [&msb[..], &[0xef, 0xcd, 0xab, 0x89, 0x67, 0x45, 0x23, 0x01]].concat();
+        unpack(&packed, 12, XI_DATA_PACK_XI_GROUPING, &mut values)?;
+        let expected: Vec<u16> = (0..16).map(|i| ((i * 0x11) << 4) | (15 - i)).collect();
+        assert_eq!(values, expected[..]);
+        // 14g224: LSBs 0, 1, .., 15 in 6 bit fields
+        let lsb = [0x40, 0x20, 0x0c, 0x44, 0x61, 0x1c, 0x48, 0xa2, 0x2c, 0x4c, 0xe3, 0x3c];
+        let packed = [&msb[..], &lsb].concat();
+        unpack(&packed, 14, XI_DATA_PACK_XI_GROUPING, &mut values)?;
+        let expected: Vec<u16> = (0..16).map(|i| ((i * 0x11) << 6) | i).collect();
+        assert_eq!(values, expected[..]);
+        // A complete group followed by a shorter one at the end of the line
+        let packed = [&msb[..], &[0xef, 0xcd, 0xab, 0x89, 0x67, 0x45, 0x23, 0x01, 0xab, 0x12, 0x3c]].concat();
+        let mut values = [0u16; 18];
+        unpack(&packed, 12, XI_DATA_PACK_XI_GROUPING, &mut values)?;
+        assert_eq!(values[15..], [0xff0, 0xabc, 0x123]);
+        assert_eq!(
+            unpack(&packed, 8, XI_DATA_PACK_XI_GROUPING, &mut values),
+            Err(XI_RET::XI_INVALID_ARG as XI_RETURN)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unpack_image() -> Result<(), XI_RETURN> {
+        use XI_OUTPUT_DATA_PACKING_TYPE::XI_DATA_PACK_PFNC_LSB_PACKING;
+        // Two rows of 2 pixels with 12 bits and one byte of padding
+        let data = vec![0xbcu8, 0x3a, 0x12, 0xee, 0x01, 0xf0, 0xff, 0xee];
+        let packed = OwnedImage {
+            xi_img: XI_IMG {
+                size: std::mem::size_of::<XI_IMG>() as u32,
+                frm: XI_IMG_FORMAT::XI_FRM_TRANSPORT_DATA,
+                width: 2,
+                height: 2,
+                padding_x: 1,
+                bp_size: data.len() as u32,
+                color_filter_array: XI_COLOR_FILTER_ARRAY::XI_CFA_BAYER_RGGB,
+                ..Default::default()
+            },
+            data,
+        };
+        let image = packed.image();
+        assert_eq!(image.packed_data().len(), 8);
+        let unpacked = image.unpack(12, XI_DATA_PACK_PFNC_LSB_PACKING)?;
+        let unpacked = unpacked.image();
+        assert_eq!(unpacked.format(), XI_IMG_FORMAT::XI_RAW16);
+        assert_eq!(unpacked.data(), [0xabc, 0x123, 0x001, 0xfff]);
+        assert_eq!(
+            image.unpack(14, XI_DATA_PACK_PFNC_LSB_PACKING).err(),
+            Some(XI_RET::XI_BUFFER_TOO_SMALL as XI_RETURN)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn planar_and_wide_formats() {
+        // 2x2 pixels in R, G and B planes with two bytes of padding per line
+        let planar = OwnedImage {
+            xi_img: XI_IMG {
+                frm: XI_IMG_FORMAT::XI_RGB16_PLANAR,
+                width: 2,
+                height: 2,
+                padding_x: 2,
+                ..Default::default()
+            },
+            data: vec![10u16, 11, 0, 12, 13, 0, 20, 21, 0, 22, 23, 0, 30, 31, 0, 32, 33, 0],
+        };
+        let image = planar.image();
+        assert!(image.is_planar());
+        assert_eq!(image.data().len(), 18);
+        assert_eq!(image.pixel(1, 1), Some(&13));
+        assert_eq!(image.sample(0, 1, 2), Some(&32));
+        assert_eq!(image.sample(0, 0, 3), None);
+        assert_eq!(image.plane(1), Some(&[20, 21, 0, 22, 23, 0][..]));
+        let interleaved = image.to_interleaved();
+        let interleaved = interleaved.image();
+        assert_eq!(interleaved.format(), XI_IMG_FORMAT::XI_RGB48);
+        assert_eq!(&interleaved.data()[..6], [30, 20, 10, 31, 21, 11]);
+        assert_eq!(interleaved.sample(1, 1, 0), Some(&33));
+        assert_eq!(interleaved.plane(0), None);
+
+        let rgb64 = OwnedImage::new(2, 1, XI_IMG_FORMAT::XI_RGB64, (0..8u16).collect()).unwrap();
+        assert_eq!(rgb64.image().pixel(1, 0), Some(&4));
+        assert_eq!(rgb64.image().sample(1, 0, 3), Some(&7));
+        assert_eq!(rgb64.image().saturation_level(), u16::MAX as f32);
+        let float = OwnedImage::new(3, 1, XI_IMG_FORMAT::XI_RAW32FLOAT, vec![0.0f32, 0.5, 1.0]).unwrap();
+        assert_eq!(float.image().pixel(2, 0), Some(&1.0));
+        assert_eq!(float.image().saturation_level(), 1.0);
+        assert!(OwnedImage::new(2, 1, XI_IMG_FORMAT::XI_RAW32, vec![1u32, 2]).is_some());
+        assert!(OwnedImage::new(2, 1, XI_IMG_FORMAT::XI_RGB_PLANAR, vec![0u8; 3]).is_none());
+    }
+
+    #[test]
+    fn process_planar_images() {
+        let mut planar = OwnedImage::new(
+            2,
+            2,
+            XI_IMG_FORMAT::XI_RGB16_PLANAR,
+            vec![10u16, 11, 12, 13, 20, 21, 22, 23, 30, 31, 32, 33],
+        )
+        .unwrap();
+        planar.xi_img.exposure_time_us = 1000;
+        assert_eq!(
+            HdrImage::merge(&[planar.clone()]).err(),
+            Some(XI_RET::XI_NOT_SUPPORTED_DATA_FORMAT as XI_RETURN)
+        );
+        let frame = CalibrationFrame::from(&planar.image());
+        assert_eq!(frame.nb_channels(), 3);
+        assert_eq!(frame.data()[..6], [30.0, 20.0, 10.0, 31.0, 21.0, 11.0]);
+        let mut defects = DefectList::new();
+        defects.push(Defect {
+            x: 1,
+            y: 1,
+            kind: DefectKind::Hot,
+        });
+        let corrected = defects.correct(&planar.image());
+        assert_eq!(corrected.image().format(), XI_IMG_FORMAT::XI_RGB48);
+        assert_eq!(corrected.image().data()[9..], [32, 22, 12]);
+    }
+
+    #[test]
+    fn rgb48_tiff_round_trip() -> std::io::Result<()> {
+        let data = (1..=6u16).map(|v| v * 1000).collect();
+        let rgb = OwnedImage::new(2, 1, XI_IMG_FORMAT::XI_RGB48, data).unwrap();
+        let mut file = std::io::Cursor::new(Vec::new());
+        rgb.image().write_tiff(&mut file, &TiffInfo::default())?;
+        file.set_position(0);
+        let (read, _) = OwnedImage::<u16>::read_tiff(&mut file)?;
+        assert_eq!(read.image().format(), XI_IMG_FORMAT::XI_RGB48);
+        assert_eq!(read.image().data(), rgb.image().data());
+        Ok(())
+    }
+
+    #[test]
+    fn image_statistics() -> Result<(), XI_RETURN> {
+        // 12 bit RAW16 image with a row of 4 pixels and 2 bytes of padding
+        let mut raw = OwnedImage {
+            xi_img: XI_IMG {
+                frm: XI_IMG_FORMAT::XI_RAW16,
+                width: 4,
+                height: 2,
+                padding_x: 2,
+                color_filter_array: XI_COLOR_FILTER_ARRAY::XI_CFA_BAYER_RGGB,
+                ..Default::default()
+            },
+            data: vec![100u16, 200, 110, 210, 9999, 300, 400, 310, 4095, 9999],
+        };
+        let config = StatisticsConfig {
+            bit_depth: Some(12),
+            ..Default::default()
+        };
+        let stats = raw.image().statistics(&config)?;
+        assert_eq!(stats.len(), 1);
+        let mono = &stats[0];
+        assert_eq!((mono.count, mono.min, mono.max), (8, 100.0, 4095.0));
+        assert_eq!(mono.mean, 5725.0 / 8.0);
+        assert_eq!(mono.histogram.bins().len(), 4096);
+        assert_eq!(mono.histogram.bins()[4095], 1);
+        assert_eq!(mono.percentile(50.0), 210.0);
+        assert_eq!(mono.percentile(100.0), 4095.0);
+
+        let bayer = StatisticsConfig {
+            bayer: true,
+            ..config
+        };
+        let stats = raw.image().statistics(&bayer)?;
+        let means: Vec<f32> = stats.iter().map(|channel| channel.mean).collect();
+        assert_eq!(means, [105.0, 205.0, 305.0, 2247.5]);
+        assert_eq!(stats[3].std_dev, 1847.5);
+        // An odd ROI offset keeps the channels at their image positions
+        let roi = StatisticsConfig {
+            roi: Some(Roi {
+                offset_x: 1,
+                offset_y: 1,
+                width: 2,
+                height: 1,
+            }),
+            ..bayer
+        };
+        let stats = raw.image().statistics(&roi)?;
+        let counts: Vec<u64> = stats.iter().map(|channel| channel.count).collect();
+        assert_eq!(counts, [0, 0, 1, 1]);
+        assert_eq!((stats[2].mean, stats[3].mean), (310.0, 400.0));
+
+        raw.xi_img.frm = XI_IMG_FORMAT::XI_FRM_TRANSPORT_DATA;
+        assert_eq!(
+            raw.image().statistics(&config).err(),
+            Some(XI_RET::XI_NOT_SUPPORTED_DATA_FORMAT as XI_RETURN)
+        );
+        let outside = StatisticsConfig {
+            roi: Some(Roi {
+                offset_x: 2,
+                offset_y: 0,
+                width: 3,
+                height: 1,
+            }),
+            ..Default::default()
+        };
+        let rgb = OwnedImage::new(2, 1, XI_IMG_FORMAT::XI_RGB24, vec![1u8, 2, 3, 5, 6, 7]).unwrap();
+        assert_eq!(rgb.image().statistics(&outside).err(), Some(XI_RET::XI_OUT_OF_RANGE as XI_RETURN));
+        let stats = rgb.image().statistics(&StatisticsConfig::default())?;
+        let means: Vec<f32> = stats.iter().map(|channel| channel.mean).collect();
+        assert_eq!(means, [3.0, 4.0, 5.0]);
+        assert_eq!(stats[0].histogram.bins().len(), 256);
+        Ok(())
+    }
+
+    #[test]
+    fn focus_scores() -> Result<(), XI_RETURN> {
+        // A sharp and a blurred vertical edge
+        let sharp: Vec<u8> = (0..64).map(|i| if i % 8 < 4 { 0 } else { 200 }).collect();
+        let blurred: Vec<u8> = (0..64).map(|i| [0, 0, 50, 100, 100, 150, 200, 200][i % 8]).collect();
+        let sharp = OwnedImage::new(8, 8, XI_IMG_FORMAT::XI_MONO8, sharp).unwrap();
+        let blurred = OwnedImage::new(8, 8, XI_IMG_FORMAT::XI_MONO8, blurred).unwrap();
+        for metric in [FocusMetric::LaplacianVariance, FocusMetric::Tenengrad, FocusMetric::Brenner] {
+            let sharp_score = sharp.image().focus_score(metric, None)?;
+            let blurred_score = blurred.image().focus_score(metric, None)?;
+            assert!(sharp_score > blurred_score, "{metric:?}: {sharp_score} <= {blurred_score}");
+        }
+        // Brenner: 2 of the 6 column pairs of every row differ by 200
+        assert_eq!(sharp.image().focus_score(FocusMetric::Brenner, None)?, 40000.0 / 3.0);
+        // A uniform region has no focus information
+        let roi = Roi {
+            offset_x: 0,
+            offset_y: 0,
+            width: 4,
+            height: 8,
+        };
+        assert_eq!(sharp.image().focus_score(FocusMetric::Tenengrad, Some(&roi))?, 0.0);
+        let outside = Roi { offset_x: 6, ..roi };
+        assert_eq!(
+            sharp.image().focus_score(FocusMetric::Brenner, Some(&outside)),
+            Err(XI_RET::XI_OUT_OF_RANGE as XI_RETURN)
+        );
+        Ok(())
+    }
+
     #[test]
     #[serial]
     fn set_exposure_during_acq() -> Result<(), XI_RETURN> {
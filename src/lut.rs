@@ -0,0 +1,143 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use std::ops::Deref;
+
+use xiapi_sys::*;
+
+use crate::Camera;
+
+/// Look-up table that maps every input pixel value (the index) to an output value.
+///
+/// Use [Camera::set_lut] to upload it to the camera.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lut {
+    values: Vec<i32>,
+}
+
+impl Lut {
+    /// Create a look-up table with `len` entries from a function of the index.
+    pub fn from_fn<F: FnMut(i32) -> i32>(len: usize, f: F) -> Self {
+        Lut {
+            values: (0..len as i32).map(f).collect(),
+        }
+    }
+
+    /// Gamma curve `max_value * (index / (len - 1))^(1 / gamma)`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let lut = xiapi::Lut::gamma(4096, 4095, 2.2);
+    /// assert_eq!(lut[0], 0);
+    /// assert_eq!(lut[4095], 4095);
+    /// ```
+    pub fn gamma(len: usize, max_value: i32, gamma: f32) -> Self {
+        let last = len.saturating_sub(1).max(1) as f32;
+        Self::from_fn(len, |index| {
+            let normalized = index as f32 / last;
+            (normalized.powf(1.0 / gamma) * max_value as f32).round() as i32
+        })
+    }
+
+    /// Inverted (negative) image
+    pub fn inverted(len: usize, max_value: i32) -> Self {
+        let last = len.saturating_sub(1).max(1) as f32;
+        Self::from_fn(len, |index| {
+            max_value - (index as f32 / last * max_value as f32).round() as i32
+        })
+    }
+
+    /// Binary image: `max_value` for all inputs at or above `threshold`, zero otherwise.
+    pub fn threshold(len: usize, max_value: i32, threshold: i32) -> Self {
+        Self::from_fn(len, |index| match index >= threshold {
+            true => max_value,
+            false => 0,
+        })
+    }
+
+    /// Identity curve with all inputs clipped to the range from `low` to `high`.
+    pub fn clip(len: usize, low: i32, high: i32) -> Self {
+        Self::from_fn(len, |index| index.clamp(low, high))
+    }
+}
+
+impl Deref for Lut {
+    type Target = [i32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.values
+    }
+}
+
+impl From<Vec<i32>> for Lut {
+    fn from(values: Vec<i32>) -> Self {
+        Lut { values }
+    }
+}
+
+impl Camera {
+    /// Upload a complete look-up table to the camera.
+    ///
+    /// The number of values must match the index range of the camera LUT
+    /// ([Self::lut_index_minimum()] to [Self::lut_index_maximum()]) and every value must be
+    /// within the range of [Self::lut_value()]. Nothing is written if the check fails.
+    /// The LUT is not enabled automatically, use [Self::set_lut_en()] for that.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// let mut cam = xiapi::open_device(None)?;
+    /// let len = cam.lut_len()?;
+    /// let max_value = cam.lut_value_maximum()?;
+    /// cam.set_lut(&xiapi::Lut::gamma(len, max_value, 2.2))?;
+    /// cam.set_lut_en(xiapi::XI_SWITCH::XI_ON)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_lut(&mut self, values: &[i32]) -> Result<(), XI_RETURN> {
+        if values.len() != self.lut_len()? {
+            return Err(XI_RET::XI_WRONG_PARAM_SIZE as XI_RETURN);
+        }
+        let value_range = self.lut_value_minimum()?..=self.lut_value_maximum()?;
+        if !values.iter().all(|value| value_range.contains(value)) {
+            return Err(XI_RET::XI_OUT_OF_RANGE as XI_RETURN);
+        }
+        let first_index = self.lut_index_minimum()?;
+        for (index, &value) in (first_index..).zip(values) {
+            self.set_lut_index(index)?;
+            self.set_lut_value(value)?;
+        }
+        Ok(())
+    }
+
+    /// Upload a look-up table computed from a function of the index.
+    ///
+    /// See also [Self::set_lut()]
+    pub fn set_lut_with<F: FnMut(i32) -> i32>(&mut self, f: F) -> Result<(), XI_RETURN> {
+        let lut = (self.lut_index_minimum()?..=self.lut_index_maximum()?)
+            .map(f)
+            .collect::<Vec<_>>();
+        self.set_lut(&lut)
+    }
+
+    /// Read the complete look-up table from the camera.
+    pub fn lut(&mut self) -> Result<Lut, XI_RETURN> {
+        let mut values = Vec::with_capacity(self.lut_len()?);
+        for index in self.lut_index_minimum()?..=self.lut_index_maximum()? {
+            self.set_lut_index(index)?;
+            values.push(self.lut_value()?);
+        }
+        Ok(Lut::from(values))
+    }
+
+    /// Number of entries in the look-up table of the camera.
+    pub fn lut_len(&self) -> Result<usize, XI_RETURN> {
+        let min = self.lut_index_minimum()?;
+        let max = self.lut_index_maximum()?;
+        Ok((max - min + 1).max(0) as usize)
+    }
+}
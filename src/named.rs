@@ -0,0 +1,131 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use std::ffi::CString;
+use std::fmt;
+use std::str::FromStr;
+
+use xiapi_sys::*;
+
+use crate::Camera;
+
+/// Value of a parameter that is accessed by its name.
+///
+/// xiAPI converts between integer, float and string values where possible, e.g. an integer
+/// parameter can also be read as float.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamValue {
+    /// Integer value, also used for enumerations and switches
+    Int(i32),
+
+    /// Floating point value
+    Float(f32),
+
+    /// String value
+    String(String),
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamValue::Int(value) => write!(f, "{}", value),
+            ParamValue::Float(value) => write!(f, "{}", value),
+            ParamValue::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl FromStr for ParamValue {
+    type Err = std::convert::Infallible;
+
+    /// Parse an integer or a float. All other text is kept as string.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        if let Ok(value) = trimmed.parse() {
+            return Ok(ParamValue::Int(value));
+        }
+        match trimmed.parse() {
+            Ok(value) => Ok(ParamValue::Float(value)),
+            Err(_) => Ok(ParamValue::String(s.to_string())),
+        }
+    }
+}
+
+/// Convert a parameter name like `"exposure"` or `"gain:max"` to a nul terminated string.
+fn param_name(name: &str) -> Result<Vec<u8>, XI_RETURN> {
+    let name = CString::new(name).or(Err(XI_RET::XI_INVALID_ARG as XI_RETURN))?;
+    Ok(name.into_bytes_with_nul())
+}
+
+impl Camera {
+    /// Read an integer parameter by its xiAPI name.
+    ///
+    /// The name may contain an info modifier, e.g. `"exposure:max"`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// let cam = xiapi::open_device(None)?;
+    /// let width = cam.int_param_by_name("width")?;
+    /// let max_width = cam.int_param_by_name("width:max")?;
+    /// assert!(width <= max_width);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn int_param_by_name(&self, name: &str) -> Result<i32, XI_RETURN> {
+        unsafe { self.param(&param_name(name)?) }
+    }
+
+    /// Read a float parameter by its xiAPI name.
+    pub fn float_param_by_name(&self, name: &str) -> Result<f32, XI_RETURN> {
+        unsafe { self.param(&param_name(name)?) }
+    }
+
+    /// Read a string parameter by its xiAPI name.
+    pub fn string_param_by_name(&self, name: &str) -> Result<String, XI_RETURN> {
+        unsafe { self.param_string(&param_name(name)?) }
+    }
+
+    /// Read a parameter by its xiAPI name without knowing its type.
+    ///
+    /// Numbers are read as float and returned as [ParamValue::Int] if they have no fractional
+    /// part. Parameters that can not be read as number are read as string.
+    pub fn param_by_name(&self, name: &str) -> Result<ParamValue, XI_RETURN> {
+        match self.float_param_by_name(name) {
+            Ok(value) if value.fract() == 0.0 && value.abs() <= i32::MAX as f32 => {
+                // Floats can not represent all integers, so integers are read again
+                Ok(self
+                    .int_param_by_name(name)
+                    .map_or(ParamValue::Float(value), ParamValue::Int))
+            }
+            Ok(value) => Ok(ParamValue::Float(value)),
+            Err(_) => self.string_param_by_name(name).map(ParamValue::String),
+        }
+    }
+
+    /// Set a parameter by its xiAPI name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// let mut cam = xiapi::open_device(None)?;
+    /// cam.set_param_by_name("exposure", &"10000".parse().unwrap())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_param_by_name(&mut self, name: &str, value: &ParamValue) -> Result<(), XI_RETURN> {
+        let name = param_name(name)?;
+        unsafe {
+            match value {
+                ParamValue::Int(value) => self.set_param(&name, *value),
+                ParamValue::Float(value) => self.set_param(&name, *value),
+                ParamValue::String(value) => self.set_param_string(&name, value),
+            }
+        }
+    }
+}
@@ -0,0 +1,218 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use xiapi_sys::*;
+
+use crate::Camera;
+use crate::Image;
+use crate::OwnedImage;
+
+/// Number of pixels in a group of `XI_DATA_PACK_XI_GROUPING`
+const GROUP_SIZE: usize = 16;
+
+impl Camera {
+    /// Request packed pixel data with the given bit depth, e.g. 10 or 12 bits.
+    ///
+    /// Images are delivered as `XI_FRM_TRANSPORT_DATA` and can be unpacked with
+    /// [Image::unpack()].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// use xiapi::XI_BIT_DEPTH::XI_BPP_12;
+    /// use xiapi::XI_OUTPUT_DATA_PACKING_TYPE::XI_DATA_PACK_PFNC_LSB_PACKING;
+    /// let mut cam = xiapi::open_device(None)?;
+    /// cam.set_packed_output(XI_BPP_12, XI_DATA_PACK_PFNC_LSB_PACKING)?;
+    /// let acq = cam.start_acquisition()?;
+    /// let image = acq.next_image::<u8>(None)?;
+    /// let unpacked = image.unpack(XI_BPP_12, XI_DATA_PACK_PFNC_LSB_PACKING)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_packed_output(
+        &mut self,
+        bit_depth: XI_BIT_DEPTH::Type,
+        packing: XI_OUTPUT_DATA_PACKING_TYPE::Type,
+    ) -> Result<(), XI_RETURN> {
+        self.set_image_data_format(XI_IMG_FORMAT::XI_FRM_TRANSPORT_DATA)?;
+        self.set_output_data_bit_depth(bit_depth)?;
+        self.set_output_data_packing(XI_SWITCH::XI_ON)?;
+        self.set_output_data_packing_type(packing)
+    }
+}
+
+impl<'a, T> Image<'a, T> {
+    /// The raw bytes of a packed image as delivered by the camera, including padding.
+    pub fn packed_data(&self) -> &[u8] {
+        self.data_bytes()
+    }
+
+    /// Unpack an `XI_FRM_TRANSPORT_DATA` image into 16 bit values.
+    ///
+    /// `bit_depth` and `packing` have to match the settings of the camera, see
+    /// [Camera::set_packed_output()]. The result is a MONO16 image, or a RAW16 image if the
+    /// sensor has a color filter array.
+    pub fn unpack(
+        &self,
+        bit_depth: XI_BIT_DEPTH::Type,
+        packing: XI_OUTPUT_DATA_PACKING_TYPE::Type,
+    ) -> Result<OwnedImage<u16>, XI_RETURN> {
+        let width = self.width() as usize;
+        let height = self.height() as usize;
+        let row_size = packed_size(width, bit_depth).ok_or(XI_RET::XI_INVALID_ARG as XI_RETURN)?;
+        let stride = row_size + self.padding_x() as usize;
+        let packed = self.packed_data();
+        if packed.len() < stride * height.saturating_sub(1) + row_size {
+            return Err(XI_RET::XI_BUFFER_TOO_SMALL as XI_RETURN);
+        }
+        let mut data = vec![0u16; width * height];
+        for (y, row) in data.chunks_exact_mut(width.max(1)).enumerate() {
+            unpack(&packed[y * stride..][..row_size], bit_depth, packing, row)?;
+        }
+        let format = match self.color_filter_array() {
+            XI_COLOR_FILTER_ARRAY::XI_CFA_NONE => XI_IMG_FORMAT::XI_MONO16,
+            _ => XI_IMG_FORMAT::XI_RAW16,
+        };
+        let mut xi_img = self.xi_img;
+        xi_img.bp = std::ptr::null_mut();
+        xi_img.frm = format;
+        xi_img.padding_x = 0;
+        xi_img.bp_size = (data.len() * 2) as u32;
+        Ok(OwnedImage { xi_img, data })
+    }
+}
+
+/// Number of bytes of `pixels` packed values with the given bit depth.
+///
+/// Returns `None` for bit depths that can not be packed.
+pub fn packed_size(pixels: usize, bit_depth: XI_BIT_DEPTH::Type) -> Option<usize> {
+    match bit_depth {
+        9..=15 => Some((pixels * bit_depth as usize).div_ceil(8)),
+        _ => None,
+    }
+}
+
+/// Unpack pixel values with 9 to 15 bits into 16 bit values.
+///
+/// With `XI_DATA_PACK_PFNC_LSB_PACKING`, the values are stored as continuous bit stream starting
+/// with the least significant bit (GenICam `Mono10p`, `Mono12p`, ...). With
+/// `XI_DATA_PACK_XI_GROUPING` (xiAPI `10g160`, `12g192`, `14g224`), the 8 most significant bits
+/// of a group of 16 pixels are stored in one byte each, followed by the remaining bits of the
+/// group as bit stream (20 bytes for 10 bits, 24 bytes for 12 bits, 28 bytes for 14 bits).
+///
+/// Unpacks `min(output.len(), values in input)` values. Returns `XI_INVALID_ARG` for unsupported
+/// bit depths or packing types.
+///
+/// # Examples
+///
+/// ```
+/// use xiapi::XI_OUTPUT_DATA_PACKING_TYPE::XI_DATA_PACK_PFNC_LSB_PACKING;
+/// let mut values = [0u16; 2];
+/// xiapi::unpack(&[0xbc, 0x3a, 0x12], 12, XI_DATA_PACK_PFNC_LSB_PACKING, &mut values)?;
+/// assert_eq!(values, [0xabc, 0x123]);
+/// # Ok::<(), xiapi::XI_RETURN>(())
+/// ```
+pub fn unpack(
+    input: &[u8],
+    bit_depth: XI_BIT_DEPTH::Type,
+    packing: XI_OUTPUT_DATA_PACKING_TYPE::Type,
+    output: &mut [u16],
+) -> Result<(), XI_RETURN> {
+    use XI_OUTPUT_DATA_PACKING_TYPE::*;
+    if packed_size(0, bit_depth).is_none() {
+        return Err(XI_RET::XI_INVALID_ARG as XI_RETURN);
+    }
+    let bits = bit_depth as usize;
+    let count = output.len().min(input.len() * 8 / bits);
+    let output = &mut output[..count];
+    match (packing, bits) {
+        (XI_DATA_PACK_PFNC_LSB_PACKING, 10) => {
+            let done = unpack_groups(input, output, |b: &[u8; 5], p: &mut [u16; 4]| {
+                p[0] = b[0] as u16 | (b[1] as u16 & 0x03) << 8;
+                p[1] = (b[1] >> 2) as u16 | (b[2] as u16 & 0x0f) << 6;
+                p[2] = (b[2] >> 4) as u16 | (b[3] as u16 & 0x3f) << 4;
+                p[3] = (b[3] >> 6) as u16 | (b[4] as u16) << 2;
+            });
+            unpack_bit_stream(&input[done * 10 / 8..], bits, &mut output[done..]);
+        }
+        (XI_DATA_PACK_PFNC_LSB_PACKING, 12) => {
+            let done = unpack_groups(input, output, |b: &[u8; 3], p: &mut [u16; 2]| {
+                p[0] = b[0] as u16 | (b[1] as u16 & 0x0f) << 8;
+                p[1] = (b[1] >> 4) as u16 | (b[2] as u16) << 4;
+            });
+            unpack_bit_stream(&input[done * 12 / 8..], bits, &mut output[done..]);
+        }
+        (XI_DATA_PACK_PFNC_LSB_PACKING, _) => unpack_bit_stream(input, bits, output),
+        (XI_DATA_PACK_XI_GROUPING, 10) => {
+            let done = unpack_groups(input, output, |b: &[u8; 20], p: &mut [u16; GROUP_SIZE]| {
+                for (i, value) in p.iter_mut().enumerate() {
+                    *value = (b[i] as u16) << 2 | (b[GROUP_SIZE + i / 4] as u16 >> (2 * (i % 4)) & 0x03);
+                }
+            });
+            unpack_grouping(&input[done * 10 / 8..], bits, &mut output[done..]);
+        }
+        (XI_DATA_PACK_XI_GROUPING, 12) => {
+            let done = unpack_groups(input, output, |b: &[u8; 24], p: &mut [u16; GROUP_SIZE]| {
+                for (i, value) in p.iter_mut().enumerate() {
+                    *value = (b[i] as u16) << 4 | (b[GROUP_SIZE + i / 2] as u16 >> (4 * (i % 2)) & 0x0f);
+                }
+            });
+            unpack_grouping(&input[done * 12 / 8..], bits, &mut output[done..]);
+        }
+        (XI_DATA_PACK_XI_GROUPING, _) => unpack_grouping(input, bits, output),
+        _ => return Err(XI_RET::XI_INVALID_ARG as XI_RETURN),
+    }
+    Ok(())
+}
+
+/// Unpack complete groups of `N` bytes into `M` values with a fixed size kernel, which the
+/// compiler can vectorize. Returns the number of unpacked values.
+fn unpack_groups<const N: usize, const M: usize, F>(input: &[u8], output: &mut [u16], kernel: F) -> usize
+where
+    F: Fn(&[u8; N], &mut [u16; M]),
+{
+    let mut done = 0;
+    for (bytes, values) in input.chunks_exact(N).zip(output.chunks_exact_mut(M)) {
+        kernel(bytes.try_into().unwrap(), values.try_into().unwrap());
+        done += M;
+    }
+    done
+}
+
+/// Unpack values from a bit stream that starts with the least significant bit.
+fn unpack_bit_stream(input: &[u8], bits: usize, output: &mut [u16]) {
+    let mask = (1u32 << bits) - 1;
+    let mut accumulator = 0u32;
+    let mut available = 0;
+    let mut bytes = input.iter();
+    for value in output {
+        while available < bits {
+            accumulator |= (*bytes.next().unwrap_or(&0) as u32) << available;
+            available += 8;
+        }
+        *value = (accumulator & mask) as u16;
+        accumulator >>= bits;
+        available -= bits;
+    }
+}
+
+/// Unpack values with XIMEA grouping: the upper 8 bits of every pixel in a group, followed by
+/// the lower bits of the group as bit stream. The last group of a line may be shorter.
+fn unpack_grouping(input: &[u8], bits: usize, output: &mut [u16]) {
+    let low_bits = bits - 8;
+    let mut offset = 0;
+    for values in output.chunks_mut(GROUP_SIZE) {
+        let high = &input[offset..(offset + values.len()).min(input.len())];
+        let low_size = (values.len() * low_bits).div_ceil(8);
+        let low_start = (offset + values.len()).min(input.len());
+        let low = &input[low_start..(low_start + low_size).min(input.len())];
+        unpack_bit_stream(low, low_bits, values);
+        for (value, &msb) in values.iter_mut().zip(high) {
+            *value |= (msb as u16) << low_bits;
+        }
+        offset += values.len() + low_size;
+    }
+}
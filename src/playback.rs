@@ -0,0 +1,207 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::mem::size_of;
+use std::ops::Range;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::sequence::FRAME_HEADER_SIZE;
+use crate::sequence::SEQUENCE_HEADER_SIZE;
+use crate::sequence::SEQUENCE_MAGIC;
+use crate::sequence::SEQUENCE_VERSION;
+use crate::FrameMetadata;
+use crate::OwnedImage;
+use crate::PixelValue;
+
+/// Position and metadata of a frame in a sequence file
+struct FrameEntry {
+    offset: u64,
+    data_size: usize,
+    metadata: FrameMetadata,
+}
+
+/// Reads frames from a sequence file that was written by a [crate::Recorder].
+///
+/// Frames are returned as [OwnedImage], so they can be processed with the same code as frames
+/// from a live acquisition. They are read in order with [Self::next_image()] or by index with
+/// [Self::read_frame()]. Playback can be limited to a range of frames and paced in real time
+/// with the recorded timestamps.
+pub struct SequenceReader {
+    file: BufReader<File>,
+    frames: Vec<FrameEntry>,
+    range: Range<usize>,
+    position: usize,
+    tick: Option<Duration>,
+    pacing_start: Option<(Instant, u64)>,
+}
+
+impl SequenceReader {
+    /// Open a sequence file and index its frames.
+    ///
+    /// An incomplete frame at the end of the file, e.g. from an interrupted recording, is ignored.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut file = BufReader::new(File::open(path)?);
+        let mut header = [0u8; SEQUENCE_HEADER_SIZE];
+        file.read_exact(&mut header)?;
+        let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+        let frame_header_size = u32::from_le_bytes(header[12..].try_into().unwrap()) as usize;
+        if &header[..8] != SEQUENCE_MAGIC || version != SEQUENCE_VERSION || frame_header_size != FRAME_HEADER_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a sequence file"));
+        }
+        let file_size = file.get_ref().metadata()?.len();
+        let mut frames = Vec::new();
+        let mut offset = SEQUENCE_HEADER_SIZE as u64;
+        let mut frame_header = [0u8; FRAME_HEADER_SIZE];
+        while offset + FRAME_HEADER_SIZE as u64 <= file_size {
+            file.seek(SeekFrom::Start(offset))?;
+            file.read_exact(&mut frame_header)?;
+            let (metadata, data_size) = FrameMetadata::read_header(&frame_header);
+            let end = offset + (FRAME_HEADER_SIZE + data_size) as u64;
+            if end > file_size {
+                break;
+            }
+            frames.push(FrameEntry {
+                offset: offset + FRAME_HEADER_SIZE as u64,
+                data_size,
+                metadata,
+            });
+            offset = end;
+        }
+        Ok(SequenceReader {
+            file,
+            range: 0..frames.len(),
+            frames,
+            position: 0,
+            tick: None,
+            pacing_start: None,
+        })
+    }
+
+    /// Number of frames in the file
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Check if the file contains no frames
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Metadata of the frame with the given index
+    pub fn metadata(&self, index: usize) -> Option<&FrameMetadata> {
+        self.frames.get(index).map(|frame| &frame.metadata)
+    }
+
+    /// Read the frame with the given index.
+    ///
+    /// This does not change the position of [Self::next_image()].
+    pub fn read_frame<T: PixelValue>(&mut self, index: usize) -> io::Result<OwnedImage<T>> {
+        let frame = self
+            .frames
+            .get(index)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "frame index out of range"))?;
+        let mut data = vec![T::default(); frame.data_size / size_of::<T>()];
+        // Pixel values are plain numbers, so any byte pattern is a valid value
+        let bytes = unsafe {
+            std::slice::from_raw_parts_mut(data.as_mut_ptr() as *mut u8, data.len() * size_of::<T>())
+        };
+        self.file.seek(SeekFrom::Start(frame.offset))?;
+        self.file.read_exact(bytes)?;
+        Ok(OwnedImage {
+            xi_img: frame.metadata.to_xi_img(bytes.len()),
+            data,
+        })
+    }
+
+    /// Get the next frame of the playback range.
+    ///
+    /// Returns `None` at the end of the range. If real-time pacing is enabled, this waits until
+    /// the frame is due according to its timestamp.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use std::time::Duration;
+    /// let mut reader = xiapi::SequenceReader::open("recording.xiseq")?;
+    /// reader.set_range(100..200);
+    /// reader.set_realtime(Some(Duration::from_nanos(4)));
+    /// while let Some(frame) = reader.next_image::<u8>()? {
+    ///     println!("Frame {}", frame.image().nframe());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn next_image<T: PixelValue>(&mut self) -> io::Result<Option<OwnedImage<T>>> {
+        if self.position >= self.range.end {
+            return Ok(None);
+        }
+        let index = self.position;
+        self.wait_until_due(index);
+        let frame = self.read_frame(index)?;
+        self.position += 1;
+        Ok(Some(frame))
+    }
+
+    /// Limit playback to a range of frame indices and seek to its start.
+    ///
+    /// The range is clamped to the frames in the file.
+    pub fn set_range(&mut self, range: Range<usize>) {
+        let end = range.end.min(self.frames.len());
+        self.range = range.start.min(end)..end;
+        self.seek(self.range.start);
+    }
+
+    /// The current playback range
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Continue playback at the given frame index.
+    ///
+    /// The index is clamped to the playback range.
+    pub fn seek(&mut self, index: usize) {
+        self.position = index.clamp(self.range.start, self.range.end);
+        self.pacing_start = None;
+    }
+
+    /// Index of the frame that is returned by the next call to [Self::next_image()]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Enable pacing of [Self::next_image()] with the recorded timestamps.
+    ///
+    /// `tick` is the duration of one unit of [FrameMetadata::timestamp_raw], which depends on the
+    /// camera series (e.g. 1 µs for xiQ, 4 ns for xiC). `None` returns frames as fast as possible.
+    pub fn set_realtime(&mut self, tick: Option<Duration>) {
+        self.tick = tick;
+        self.pacing_start = None;
+    }
+
+    fn wait_until_due(&mut self, index: usize) {
+        let tick = match self.tick {
+            Some(tick) => tick,
+            None => return,
+        };
+        let timestamp = self.frames[index].metadata.timestamp_raw;
+        let (start, first_timestamp) = *self.pacing_start.get_or_insert((Instant::now(), timestamp));
+        let ticks = timestamp.saturating_sub(first_timestamp) as u128;
+        let offset = (tick.as_nanos() * ticks).min(u64::MAX as u128) as u64;
+        let due = start + Duration::from_nanos(offset);
+        let now = Instant::now();
+        if due > now {
+            thread::sleep(due - now);
+        }
+    }
+}
@@ -0,0 +1,287 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use std::time::Instant;
+
+use xiapi_sys::*;
+
+use crate::sequence::sequence_header;
+use crate::sequence::FRAME_HEADER_SIZE;
+use crate::sequence::SEQUENCE_HEADER_SIZE;
+use crate::AcquisitionBuffer;
+use crate::FrameMetadata;
+use crate::Image;
+
+/// Settings of a [Recorder]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecorderConfig {
+    /// Number of frames that can be waiting to be written. The memory for these frames is
+    /// allocated when the recorder is created.
+    pub pool_size: usize,
+
+    /// Number of threads that write frames to the file in parallel
+    pub writer_threads: usize,
+}
+
+impl Default for RecorderConfig {
+    fn default() -> Self {
+        RecorderConfig {
+            pool_size: 64,
+            writer_threads: 2,
+        }
+    }
+}
+
+/// Progress of a [Recorder]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecorderStats {
+    /// Frames that were accepted for writing
+    pub recorded_frames: u64,
+
+    /// Frames that were dropped because all buffers of the pool were waiting to be written
+    pub dropped_frames: u64,
+
+    /// Bytes written to the file, including the headers
+    pub bytes_written: u64,
+
+    /// Time since the recorder was created
+    pub elapsed: Duration,
+}
+
+impl RecorderStats {
+    /// Average write throughput in bytes per second
+    pub fn throughput(&self) -> f64 {
+        match self.elapsed.as_secs_f64() {
+            seconds if seconds > 0.0 => self.bytes_written as f64 / seconds,
+            _ => 0.0,
+        }
+    }
+}
+
+/// A frame that is waiting to be written
+struct WriteJob {
+    offset: u64,
+    buffer: Vec<u8>,
+}
+
+/// State shared with the writer threads
+#[derive(Default)]
+struct Shared {
+    bytes_written: AtomicU64,
+    error: Mutex<Option<io::Error>>,
+}
+
+/// Records frames to a sequence file at a sustained rate.
+///
+/// Frames are copied into buffers from a pool that is allocated when the recorder is created.
+/// The buffers are written by separate threads, so that the acquisition is not blocked by the
+/// disk. If the disk can not keep up and no buffer is free, frames are dropped and counted in
+/// [RecorderStats::dropped_frames]. Every frame is stored with its [FrameMetadata].
+///
+/// Each frame gets its position in the file when it is recorded, so the writer threads can
+/// write in parallel and the frames are always stored in the order they were recorded.
+pub struct Recorder {
+    file: Arc<File>,
+    next_offset: u64,
+    free_buffers: Receiver<Vec<u8>>,
+    jobs: Option<Sender<WriteJob>>,
+    writers: Vec<JoinHandle<()>>,
+    shared: Arc<Shared>,
+    recorded_frames: u64,
+    dropped_frames: u64,
+    started: Instant,
+}
+
+impl Recorder {
+    /// Create a sequence file for frames of up to `frame_size` bytes.
+    ///
+    /// Use [Self::for_acquisition()] to get the frame size from the camera.
+    pub fn create<P: AsRef<Path>>(path: P, frame_size: usize, config: RecorderConfig) -> io::Result<Self> {
+        let mut file = File::create(path)?;
+        file.write_all(&sequence_header())?;
+        let file = Arc::new(file);
+        let shared = Arc::new(Shared::default());
+        let (free_sender, free_buffers) = mpsc::channel();
+        for _ in 0..config.pool_size.max(1) {
+            let _ = free_sender.send(Vec::with_capacity(FRAME_HEADER_SIZE + frame_size));
+        }
+        let (jobs, job_receiver) = mpsc::channel::<WriteJob>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let writers = (0..config.writer_threads.max(1))
+            .map(|_| {
+                let file = file.clone();
+                let shared = shared.clone();
+                let job_receiver = job_receiver.clone();
+                let free_sender = free_sender.clone();
+                thread::spawn(move || loop {
+                    let job = match job_receiver.lock().unwrap().recv() {
+                        Ok(job) => job,
+                        Err(_) => return,
+                    };
+                    match write_all_at(&file, &job.buffer, job.offset) {
+                        Ok(()) => {
+                            shared
+                                .bytes_written
+                                .fetch_add(job.buffer.len() as u64, Ordering::Relaxed);
+                        }
+                        Err(err) => {
+                            shared.error.lock().unwrap().get_or_insert(err);
+                        }
+                    }
+                    let _ = free_sender.send(job.buffer);
+                })
+            })
+            .collect();
+        Ok(Recorder {
+            file,
+            next_offset: SEQUENCE_HEADER_SIZE as u64,
+            free_buffers,
+            jobs: Some(jobs),
+            writers,
+            shared,
+            recorded_frames: 0,
+            dropped_frames: 0,
+            started: Instant::now(),
+        })
+    }
+
+    /// Create a sequence file for the frames of a running acquisition.
+    pub fn for_acquisition<P: AsRef<Path>>(
+        path: P,
+        buffer: &AcquisitionBuffer,
+        config: RecorderConfig,
+    ) -> io::Result<Self> {
+        let frame_size = buffer.image_payload_size().map_err(xiapi_error)?;
+        Self::create(path, frame_size.max(0) as usize, config)
+    }
+
+    /// Copy a frame into the pool and queue it for writing.
+    ///
+    /// Returns `false` if the frame was dropped because no buffer was free. Errors of the writer
+    /// threads are returned by the next call.
+    pub fn record<T>(&mut self, image: &Image<'_, T>) -> io::Result<bool> {
+        self.check_error()?;
+        let mut buffer = match self.free_buffers.try_recv() {
+            Ok(buffer) => buffer,
+            Err(_) => {
+                self.dropped_frames += 1;
+                return Ok(false);
+            }
+        };
+        let data = image.data_bytes();
+        buffer.clear();
+        FrameMetadata::of(image).write_header(data.len(), &mut buffer);
+        buffer.extend_from_slice(data);
+        let offset = self.next_offset;
+        self.next_offset += buffer.len() as u64;
+        self.jobs
+            .as_ref()
+            .and_then(|jobs| jobs.send(WriteJob { offset, buffer }).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "writer threads stopped"))?;
+        self.recorded_frames += 1;
+        Ok(true)
+    }
+
+    /// Record `count` frames from a running acquisition.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut cam = xiapi::open_device(None).map_err(|err| format!("xiAPI error {err}"))?;
+    /// cam.set_framerate(1000.0).map_err(|err| format!("xiAPI error {err}"))?;
+    /// let acq = cam.start_acquisition().map_err(|err| format!("xiAPI error {err}"))?;
+    /// let mut recorder = xiapi::Recorder::for_acquisition("recording.xiseq", &acq, Default::default())?;
+    /// recorder.record_from::<u8>(&acq, 10000, Some(1000))?;
+    /// let stats = recorder.finish()?;
+    /// println!("{} frames dropped, {} MB/s", stats.dropped_frames, stats.throughput() / 1e6);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn record_from<T>(&mut self, buffer: &AcquisitionBuffer, count: usize, timeout: Option<u32>) -> io::Result<()> {
+        for _ in 0..count {
+            let image = buffer.next_image::<T>(timeout).map_err(xiapi_error)?;
+            self.record(&image)?;
+        }
+        Ok(())
+    }
+
+    /// Current progress of the recording
+    pub fn stats(&self) -> RecorderStats {
+        RecorderStats {
+            recorded_frames: self.recorded_frames,
+            dropped_frames: self.dropped_frames,
+            bytes_written: SEQUENCE_HEADER_SIZE as u64 + self.shared.bytes_written.load(Ordering::Relaxed),
+            elapsed: self.started.elapsed(),
+        }
+    }
+
+    /// Wait until all frames are written, close the file and return the final statistics.
+    pub fn finish(mut self) -> io::Result<RecorderStats> {
+        self.stop_writers();
+        self.check_error()?;
+        self.file.sync_data()?;
+        Ok(self.stats())
+    }
+
+    fn stop_writers(&mut self) {
+        // The writer threads end when the queue is closed and empty
+        self.jobs = None;
+        for writer in self.writers.drain(..) {
+            let _ = writer.join();
+        }
+    }
+
+    fn check_error(&self) -> io::Result<()> {
+        match self.shared.error.lock().unwrap().take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.stop_writers();
+    }
+}
+
+fn xiapi_error(err: XI_RETURN) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("xiAPI error {err}"))
+}
+
+#[cfg(unix)]
+fn write_all_at(file: &File, buffer: &[u8], offset: u64) -> io::Result<()> {
+    std::os::unix::fs::FileExt::write_all_at(file, buffer, offset)
+}
+
+#[cfg(windows)]
+fn write_all_at(file: &File, mut buffer: &[u8], mut offset: u64) -> io::Result<()> {
+    use std::os::windows::fs::FileExt;
+    while !buffer.is_empty() {
+        match file.seek_write(buffer, offset)? {
+            0 => return Err(io::ErrorKind::WriteZero.into()),
+            written => {
+                buffer = &buffer[written..];
+                offset += written as u64;
+            }
+        }
+    }
+    Ok(())
+}
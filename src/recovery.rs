@@ -0,0 +1,182 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use xiapi_sys::*;
+
+use crate::open_device_by_serial;
+use crate::AcquisitionBuffer;
+use crate::Camera;
+
+/// Size of the buffer used to read the parameter context of a camera
+const MAX_CONTEXT_SIZE: usize = 1024 * 1024;
+
+/// How an error returned by the camera can be handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// The operation can be retried, e.g. after a timeout
+    Transient,
+
+    /// The image acquisition has to be stopped and started again
+    RestartAcquisition,
+
+    /// The connection to the camera was lost. The camera has to be opened again.
+    DeviceLost,
+
+    /// Errors that are not caused by the connection, e.g. invalid parameter values
+    Other,
+}
+
+impl ErrorClass {
+    /// Classify an error code returned by xiAPI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xiapi::ErrorClass;
+    /// let timeout = xiapi::XI_RET::XI_TIMEOUT as xiapi::XI_RETURN;
+    /// assert_eq!(ErrorClass::of(timeout), ErrorClass::Transient);
+    /// ```
+    pub fn of(err: XI_RETURN) -> Self {
+        use XI_RET::*;
+        match err as XI_RET::Type {
+            XI_TIMEOUT | XI_NO_IMAGE | XI_DEVICE_NOT_READY | XI_TGBUSY => ErrorClass::Transient,
+            XI_ACQUISITION_STOPED
+            | XI_ACQUISITION_STOPED_WERR
+            | XI_READBLK
+            | XI_WRITEBLK
+            | XI_ISOCH_ATTACH_BUFFERS
+            | XI_GET_OVERLAPPED_RESULT
+            | XI_INVALID_BUFFER => ErrorClass::RestartAcquisition,
+            XI_INVALID_HANDLE
+            | XI_READREG
+            | XI_WRITEREG
+            | XI_IO_WRONG
+            | XI_ERRORCAMCONTEXT
+            | XI_BUS_RESET_FAILED
+            | XI_DEVICE_HAS_BEEN_RESETED
+            | XI_NO_DEVICES_FOUND => ErrorClass::DeviceLost,
+            _ => ErrorClass::Other,
+        }
+    }
+}
+
+/// Everything that is required to open a camera again with the same parameters.
+///
+/// As the parameters can not be read from a camera that was disconnected, the recovery point
+/// has to be created while the camera is still working, e.g. right after it was configured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryPoint {
+    serial_number: String,
+    parameters: String,
+}
+
+impl RecoveryPoint {
+    /// Serial number of the camera
+    pub fn serial_number(&self) -> &str {
+        &self.serial_number
+    }
+
+    /// Parameters of the camera in the format of [Camera::parameter_context]
+    pub fn parameters(&self) -> &str {
+        &self.parameters
+    }
+
+    /// Open the camera by its serial number and restore the parameters.
+    pub fn reopen(&self) -> Result<Camera, XI_RETURN> {
+        let mut cam = open_device_by_serial(&self.serial_number)?;
+        cam.set_parameter_context(&self.parameters)?;
+        Ok(cam)
+    }
+}
+
+impl Camera {
+    /// Read all parameters of the camera as text.
+    ///
+    /// The text can be stored and applied to the same camera model with
+    /// [Self::set_parameter_context()].
+    pub fn parameter_context(&self) -> Result<String, XI_RETURN> {
+        unsafe { self.param_string_sized(XI_PRM_API_CONTEXT_LIST, MAX_CONTEXT_SIZE) }
+    }
+
+    /// Apply parameters that were read with [Self::parameter_context()].
+    pub fn set_parameter_context(&mut self, context: &str) -> Result<(), XI_RETURN> {
+        unsafe { self.set_param_string(XI_PRM_API_CONTEXT_LIST, context) }
+    }
+
+    /// Save the serial number and the current parameters to reopen the camera after it was lost.
+    pub fn recovery_point(&self) -> Result<RecoveryPoint, XI_RETURN> {
+        Ok(RecoveryPoint {
+            serial_number: self.serial_number()?,
+            parameters: self.parameter_context()?,
+        })
+    }
+}
+
+impl AcquisitionBuffer {
+    /// Check if the camera is acquiring images.
+    pub fn is_acquiring(&mut self) -> Result<bool, XI_RETURN> {
+        let status = self.camera.with_selector(
+            XI_PRM_ACQUISITION_STATUS_SELECTOR,
+            XI_ACQUISITION_STATUS_SELECTOR::XI_ACQUISITION_STATUS_ACQ_ACTIVE,
+            |cam| cam.acquisition_status(),
+        )?;
+        Ok(status == XI_SWITCH::XI_ON)
+    }
+
+    /// Stop and start the image acquisition again.
+    ///
+    /// Images that were not read from the buffer are lost. Errors while stopping are ignored, as
+    /// the acquisition may already have been stopped by the error that is recovered from.
+    pub fn restart_acquisition(&mut self) -> Result<(), XI_RETURN> {
+        let handle = *self.camera;
+        unsafe { xiapi_sys::xiStopAcquisition(handle) };
+        let err = unsafe { xiapi_sys::xiStartAcquisition(handle) };
+        match err as XI_RET::Type {
+            XI_RET::XI_OK => Ok(()),
+            _ => Err(err),
+        }
+    }
+
+    /// Recover from an error returned by this acquisition buffer.
+    ///
+    /// Depending on the [ErrorClass] of the error, nothing is done, the acquisition is restarted,
+    /// or the camera is opened again with the parameters of the recovery point and a new
+    /// acquisition is started. Errors of class [ErrorClass::Other] are returned unchanged.
+    ///
+    /// If the recovery fails, the error is returned and the acquisition buffer stays usable, so
+    /// the recovery can be tried again. A lost camera is closed in that case and every call
+    /// returns an error until it was opened again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// let cam = xiapi::open_device(None)?;
+    /// let recovery_point = cam.recovery_point()?;
+    /// let mut acq = cam.start_acquisition()?;
+    /// for _ in 0..100 {
+    ///     let result = acq.next_image::<u8>(Some(1000)).map(|image| image.nframe());
+    ///     match result {
+    ///         Ok(nframe) => println!("Received frame {nframe}"),
+    ///         Err(err) => acq.recover(err, &recovery_point)?,
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn recover(&mut self, err: XI_RETURN, recovery_point: &RecoveryPoint) -> Result<(), XI_RETURN> {
+        match ErrorClass::of(err) {
+            ErrorClass::Transient => Ok(()),
+            ErrorClass::RestartAcquisition => self.restart_acquisition(),
+            ErrorClass::DeviceLost => {
+                // The old handle has to be closed before the camera can be opened again
+                self.camera.close();
+                *self = recovery_point.reopen()?.start_acquisition()?;
+                Ok(())
+            }
+            ErrorClass::Other => Err(err),
+        }
+    }
+}
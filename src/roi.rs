@@ -1,4 +1,5 @@
 /// Roi represents a region of interest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Roi {
     /// Offset from the left in the horizontal direction
     pub offset_x: u32,
@@ -0,0 +1,160 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use std::ops::Deref;
+use std::ops::DerefMut;
+
+use xiapi_sys::*;
+
+use crate::Camera;
+
+/// Temporarily changes a selector parameter of a camera.
+///
+/// Many xiAPI parameters depend on a selector (e.g. [Camera::gain()] on
+/// [Camera::gain_selector()]). The guard gives access to the camera with the selector set and
+/// restores the previous selector when it is dropped, also if an error occurred in between.
+/// Use [Self::restore()] to handle errors while restoring the selector.
+///
+/// Created by [Camera::select].
+pub struct SelectorGuard<'a> {
+    camera: &'a mut Camera,
+    param: &'static [u8],
+    previous: Option<u32>,
+}
+
+impl SelectorGuard<'_> {
+    /// Restore the previous selector and return any error that occurs.
+    pub fn restore(mut self) -> Result<(), XI_RETURN> {
+        match self.previous.take() {
+            Some(previous) => unsafe { self.camera.set_param(self.param, previous) },
+            None => Ok(()),
+        }
+    }
+}
+
+impl Deref for SelectorGuard<'_> {
+    type Target = Camera;
+
+    fn deref(&self) -> &Self::Target {
+        self.camera
+    }
+}
+
+impl DerefMut for SelectorGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.camera
+    }
+}
+
+impl Drop for SelectorGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            let _ = unsafe { self.camera.set_param(self.param, previous) };
+        }
+    }
+}
+
+impl Camera {
+    /// Set a selector parameter until the returned guard is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// use xiapi::XI_PRM_GAIN_SELECTOR;
+    /// use xiapi::XI_GAIN_SELECTOR_TYPE::XI_GAIN_SELECTOR_ANALOG_ALL;
+    /// let mut cam = xiapi::open_device(None)?;
+    /// let mut analog = cam.select(XI_PRM_GAIN_SELECTOR, XI_GAIN_SELECTOR_ANALOG_ALL)?;
+    /// analog.set_gain(6.0)?;
+    /// analog.restore()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn select(&mut self, param: &'static [u8], value: u32) -> Result<SelectorGuard<'_>, XI_RETURN> {
+        let previous = unsafe { self.param::<u32>(param) }?;
+        unsafe { self.set_param(param, value) }?;
+        Ok(SelectorGuard {
+            camera: self,
+            param,
+            previous: Some(previous),
+        })
+    }
+
+    /// Call `f` with a selector parameter set and restore the previous selector afterwards.
+    ///
+    /// If `f` fails, its error is returned even if restoring the selector fails as well.
+    pub fn with_selector<R, F>(&mut self, param: &'static [u8], value: u32, f: F) -> Result<R, XI_RETURN>
+    where
+        F: FnOnce(&mut Camera) -> Result<R, XI_RETURN>,
+    {
+        let mut guard = self.select(param, value)?;
+        let result = f(&mut guard);
+        let restored = guard.restore();
+        let result = result?;
+        restored?;
+        Ok(result)
+    }
+
+    /// Read the gain of the given type in dB.
+    pub fn gain_of(&mut self, selector: XI_GAIN_SELECTOR_TYPE::Type) -> Result<f32, XI_RETURN> {
+        self.with_selector(XI_PRM_GAIN_SELECTOR, selector, |cam| cam.gain())
+    }
+
+    /// Set the gain of the given type in dB.
+    pub fn set_gain_of(&mut self, selector: XI_GAIN_SELECTOR_TYPE::Type, value: f32) -> Result<(), XI_RETURN> {
+        self.with_selector(XI_PRM_GAIN_SELECTOR, selector, |cam| cam.set_gain(value))
+    }
+
+    /// Read the exposure time of the given group in microseconds.
+    pub fn exposure_of(&mut self, selector: XI_EXPOSURE_TIME_SELECTOR_TYPE::Type) -> Result<f32, XI_RETURN> {
+        self.with_selector(XI_PRM_EXPOSURE_TIME_SELECTOR, selector, |cam| cam.exposure())
+    }
+
+    /// Set the exposure time of the given group in microseconds.
+    pub fn set_exposure_of(
+        &mut self,
+        selector: XI_EXPOSURE_TIME_SELECTOR_TYPE::Type,
+        value: f32,
+    ) -> Result<(), XI_RETURN> {
+        self.with_selector(XI_PRM_EXPOSURE_TIME_SELECTOR, selector, |cam| cam.set_exposure(value))
+    }
+
+    /// Read the temperature of the given sensor in degree Celsius.
+    pub fn temperature(&mut self, selector: XI_TEMP_SELECTOR::Type) -> Result<f32, XI_RETURN> {
+        self.with_selector(XI_PRM_TEMP_SELECTOR, selector, |cam| cam.temp())
+    }
+
+    /// Read the value of a sensor specific feature.
+    pub fn sensor_feature(&mut self, selector: XI_SENSOR_FEATURE_SELECTOR::Type) -> Result<i32, XI_RETURN> {
+        self.with_selector(XI_PRM_SENSOR_FEATURE_SELECTOR, selector, |cam| {
+            cam.sensor_feature_value()
+        })
+    }
+
+    /// Set the value of a sensor specific feature.
+    pub fn set_sensor_feature(
+        &mut self,
+        selector: XI_SENSOR_FEATURE_SELECTOR::Type,
+        value: i32,
+    ) -> Result<(), XI_RETURN> {
+        self.with_selector(XI_PRM_SENSOR_FEATURE_SELECTOR, selector, |cam| {
+            cam.set_sensor_feature_value(value)
+        })
+    }
+
+    /// Read the function of the given LED.
+    pub fn led_mode_of(&mut self, selector: XI_LED_SELECTOR::Type) -> Result<XI_LED_MODE::Type, XI_RETURN> {
+        self.with_selector(XI_PRM_LED_SELECTOR, selector, |cam| cam.led_mode())
+    }
+
+    /// Set the function of the given LED.
+    pub fn set_led_mode_of(
+        &mut self,
+        selector: XI_LED_SELECTOR::Type,
+        mode: XI_LED_MODE::Type,
+    ) -> Result<(), XI_RETURN> {
+        self.with_selector(XI_PRM_LED_SELECTOR, selector, |cam| cam.set_led_mode(mode))
+    }
+}
@@ -0,0 +1,177 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use std::mem::size_of;
+
+use xiapi_sys::*;
+
+use crate::Image;
+
+/// First bytes of a sequence file
+pub(crate) const SEQUENCE_MAGIC: &[u8; 8] = b"XISEQ\0\0\0";
+
+/// Version of the sequence file format
+pub(crate) const SEQUENCE_VERSION: u32 = 1;
+
+/// Size of the file header: magic, version and size of the frame headers
+pub(crate) const SEQUENCE_HEADER_SIZE: usize = 16;
+
+/// Size of the header in front of the data of every frame
+pub(crate) const FRAME_HEADER_SIZE: usize = 72;
+
+/// Acquisition metadata of a recorded frame.
+///
+/// A sequence file starts with a header of 16 bytes (magic `"XISEQ"`, format version and frame
+/// header size as little endian `u32`). It is followed by the frames, each consisting of a frame
+/// header and the image data as received from the camera, including the padding of the rows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameMetadata {
+    /// Frame number, see [Image::nframe]
+    pub nframe: u32,
+
+    /// Acquisition frame number, see [Image::acq_nframe]
+    pub acq_nframe: u32,
+
+    /// Raw timestamp, see [Image::timestamp_raw]
+    pub timestamp_raw: u64,
+
+    /// Exposure time in microseconds
+    pub exposure_time_us: u32,
+
+    /// Gain in dB
+    pub gain_db: f32,
+
+    /// Format of the image data
+    pub format: XI_IMG_FORMAT::Type,
+
+    /// Format in which the data was transferred from the camera
+    pub transport_format: XI_IMG_FORMAT::Type,
+
+    /// Width of the image in pixels
+    pub width: u32,
+
+    /// Height of the image in pixels
+    pub height: u32,
+
+    /// Number of bytes at the end of every row
+    pub padding_x: u32,
+
+    /// Black level of the image data
+    pub black_level: u32,
+
+    /// Color filter array of RAW images
+    pub color_filter_array: XI_COLOR_FILTER_ARRAY::Type,
+
+    /// User data that was set with [crate::Camera::set_image_user_data]
+    pub image_user_data: u32,
+
+    /// Pixel value at which the sensor is saturated
+    pub data_saturation: f64,
+}
+
+impl FrameMetadata {
+    /// Read the metadata of an image.
+    pub fn of<T>(image: &Image<'_, T>) -> Self {
+        FrameMetadata {
+            nframe: image.nframe(),
+            acq_nframe: image.acq_nframe(),
+            timestamp_raw: image.timestamp_raw(),
+            exposure_time_us: image.exposure_time_us(),
+            gain_db: image.gain_db(),
+            format: image.format(),
+            transport_format: image.transport_format(),
+            width: image.width(),
+            height: image.height(),
+            padding_x: image.padding_x(),
+            black_level: image.black_level(),
+            color_filter_array: image.color_filter_array(),
+            image_user_data: image.image_user_data(),
+            data_saturation: image.data_saturation(),
+        }
+    }
+
+    /// Create the image description for frame data with this metadata.
+    pub(crate) fn to_xi_img(self, data_size: usize) -> XI_IMG {
+        XI_IMG {
+            size: size_of::<XI_IMG>() as u32,
+            bp_size: data_size as u32,
+            frm: self.format,
+            width: self.width,
+            height: self.height,
+            nframe: self.nframe,
+            tsSec: (self.timestamp_raw >> 32) as u32,
+            tsUSec: self.timestamp_raw as u32,
+            black_level: self.black_level,
+            padding_x: self.padding_x,
+            transport_frm: self.transport_format,
+            exposure_time_us: self.exposure_time_us,
+            gain_db: self.gain_db,
+            acq_nframe: self.acq_nframe,
+            image_user_data: self.image_user_data,
+            data_saturation: self.data_saturation,
+            color_filter_array: self.color_filter_array,
+            ..Default::default()
+        }
+    }
+
+    /// Append the frame header for `data_size` bytes of image data.
+    pub(crate) fn write_header(&self, data_size: usize, buffer: &mut Vec<u8>) {
+        let start = buffer.len();
+        buffer.extend_from_slice(&(data_size as u64).to_le_bytes());
+        buffer.extend_from_slice(&self.nframe.to_le_bytes());
+        buffer.extend_from_slice(&self.acq_nframe.to_le_bytes());
+        buffer.extend_from_slice(&self.timestamp_raw.to_le_bytes());
+        buffer.extend_from_slice(&self.exposure_time_us.to_le_bytes());
+        buffer.extend_from_slice(&self.gain_db.to_le_bytes());
+        buffer.extend_from_slice(&self.format.to_le_bytes());
+        buffer.extend_from_slice(&self.transport_format.to_le_bytes());
+        buffer.extend_from_slice(&self.width.to_le_bytes());
+        buffer.extend_from_slice(&self.height.to_le_bytes());
+        buffer.extend_from_slice(&self.padding_x.to_le_bytes());
+        buffer.extend_from_slice(&self.black_level.to_le_bytes());
+        buffer.extend_from_slice(&self.color_filter_array.to_le_bytes());
+        buffer.extend_from_slice(&self.image_user_data.to_le_bytes());
+        buffer.extend_from_slice(&self.data_saturation.to_le_bytes());
+        debug_assert_eq!(buffer.len() - start, FRAME_HEADER_SIZE);
+    }
+
+    /// Parse a frame header and return the metadata and the size of the image data.
+    pub(crate) fn read_header(header: &[u8; FRAME_HEADER_SIZE]) -> (Self, usize) {
+        let mut position = 0;
+        let mut next = |length: usize| {
+            let bytes = &header[position..position + length];
+            position += length;
+            bytes
+        };
+        let u32_at = |bytes: &[u8]| u32::from_le_bytes(bytes.try_into().unwrap());
+        let u64_at = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap());
+        let data_size = u64_at(next(8)) as usize;
+        let metadata = FrameMetadata {
+            nframe: u32_at(next(4)),
+            acq_nframe: u32_at(next(4)),
+            timestamp_raw: u64_at(next(8)),
+            exposure_time_us: u32_at(next(4)),
+            gain_db: f32::from_bits(u32_at(next(4))),
+            format: u32_at(next(4)),
+            transport_format: u32_at(next(4)),
+            width: u32_at(next(4)),
+            height: u32_at(next(4)),
+            padding_x: u32_at(next(4)),
+            black_level: u32_at(next(4)),
+            color_filter_array: u32_at(next(4)),
+            image_user_data: u32_at(next(4)),
+            data_saturation: f64::from_bits(u64_at(next(8))),
+        };
+        (metadata, data_size)
+    }
+}
+
+/// Header at the start of a sequence file
+pub(crate) fn sequence_header() -> [u8; SEQUENCE_HEADER_SIZE] {
+    let mut header = [0u8; SEQUENCE_HEADER_SIZE];
+    header[..8].copy_from_slice(SEQUENCE_MAGIC);
+    header[8..12].copy_from_slice(&SEQUENCE_VERSION.to_le_bytes());
+    header[12..].copy_from_slice(&(FRAME_HEADER_SIZE as u32).to_le_bytes());
+    header
+}
@@ -0,0 +1,262 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::mem::size_of;
+use std::path::Path;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use xiapi_sys::*;
+
+use crate::AcquisitionBuffer;
+use crate::Image;
+use crate::PixelValue;
+
+/// Size of the SER file header
+const HEADER_SIZE: usize = 178;
+
+/// .NET ticks (100 ns since 0001-01-01) at the Unix epoch
+const UNIX_EPOCH_TICKS: u64 = 621_355_968_000_000_000;
+
+// Color IDs of the SER format
+const SER_MONO: i32 = 0;
+const SER_BAYER_RGGB: i32 = 8;
+const SER_BAYER_GRBG: i32 = 9;
+const SER_BAYER_GBRG: i32 = 10;
+const SER_BAYER_BGGR: i32 = 11;
+const SER_BGR: i32 = 101;
+
+/// Information stored in the header of a SER file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SerInfo {
+    /// Name of the observer, up to 40 characters
+    pub observer: String,
+
+    /// Name of the camera, up to 40 characters
+    pub instrument: String,
+
+    /// Name of the telescope, up to 40 characters
+    pub telescope: String,
+
+    /// Number of bits of the pixel values. The full range of the pixel type is used if this is
+    /// `None`.
+    pub bit_depth: Option<u16>,
+
+    /// Duration of one unit of [Image::timestamp_raw], which depends on the camera series (e.g.
+    /// 1 µs for xiQ, 4 ns for xiC). If this is `None`, frames are timestamped with the time at
+    /// which they are written.
+    pub timestamp_tick: Option<Duration>,
+}
+
+/// Layout of the frames in a SER file, taken from the first frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SerLayout {
+    width: u32,
+    height: u32,
+    format: XI_IMG_FORMAT::Type,
+    color_id: i32,
+    bits: u16,
+}
+
+/// Writes frames into a SER video file as used for planetary imaging.
+///
+/// MONO, RAW (as Bayer) and interleaved RGB images with 8 or 16 bits are supported. All frames have to
+/// have the same size and format. The header is completed and the timestamps are appended when
+/// the writer is finished.
+pub struct SerWriter<W: Write + Seek> {
+    writer: W,
+    info: SerInfo,
+    layout: Option<SerLayout>,
+    timestamps: Vec<u64>,
+    time_reference: Option<(u64, u64)>,
+}
+
+impl SerWriter<BufWriter<File>> {
+    /// Create a SER file.
+    pub fn create<P: AsRef<Path>>(path: P, info: SerInfo) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), info)
+    }
+}
+
+impl<W: Write + Seek> SerWriter<W> {
+    /// Start a SER file in the given writer.
+    pub fn new(mut writer: W, info: SerInfo) -> io::Result<Self> {
+        // The header is written when the number of frames is known
+        writer.write_all(&[0u8; HEADER_SIZE])?;
+        Ok(SerWriter {
+            writer,
+            info,
+            layout: None,
+            timestamps: Vec::new(),
+            time_reference: None,
+        })
+    }
+
+    /// Append a frame.
+    pub fn write_frame<T: PixelValue>(&mut self, image: &Image<'_, T>) -> io::Result<()> {
+        let layout = SerLayout::of(image, self.info.bit_depth)?;
+        if *self.layout.get_or_insert(layout) != layout {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "all frames of a SER file must have the same size and format",
+            ));
+        }
+        let order: &[usize] = match layout.color_id {
+            // BGR order of the camera is kept, an alpha channel is dropped
+            SER_BGR => &[0, 1, 2],
+            _ => &[0],
+        };
+        self.writer.write_all(&image.packed_samples(image.nb_channels(), order))?;
+        let timestamp = self.timestamp(image.timestamp_raw());
+        self.timestamps.push(timestamp);
+        Ok(())
+    }
+
+    /// Append `count` frames from a running acquisition.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use xiapi::XI_IMG_FORMAT::XI_RAW8;
+    /// let mut cam = xiapi::open_device(None).map_err(|err| format!("xiAPI error {err}"))?;
+    /// cam.set_image_data_format(XI_RAW8).map_err(|err| format!("xiAPI error {err}"))?;
+    /// let acq = cam.start_acquisition().map_err(|err| format!("xiAPI error {err}"))?;
+    /// let info = xiapi::SerInfo {
+    ///     instrument: "XIMEA".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let mut ser = xiapi::SerWriter::create("jupiter.ser", info)?;
+    /// ser.record_from::<u8>(&acq, 1000, Some(1000))?;
+    /// ser.finish()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn record_from<T: PixelValue>(
+        &mut self,
+        buffer: &AcquisitionBuffer,
+        count: usize,
+        timeout: Option<u32>,
+    ) -> io::Result<()> {
+        for _ in 0..count {
+            let image = buffer
+                .next_image::<T>(timeout)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, format!("xiAPI error {err}")))?;
+            self.write_frame(&image)?;
+        }
+        Ok(())
+    }
+
+    /// Number of frames written so far
+    pub fn frame_count(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    /// Write the timestamps and the header and return the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        for timestamp in &self.timestamps {
+            self.writer.write_all(&timestamp.to_le_bytes())?;
+        }
+        let header = self.header();
+        self.writer.seek(SeekFrom::Start(0))?;
+        self.writer.write_all(&header)?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Time of a frame in .NET ticks
+    fn timestamp(&mut self, timestamp_raw: u64) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| (time.as_nanos() / 100) as u64)
+            + UNIX_EPOCH_TICKS;
+        let tick = match self.info.timestamp_tick {
+            Some(tick) => tick,
+            None => return now,
+        };
+        let (reference, first_raw) = *self.time_reference.get_or_insert((now, timestamp_raw));
+        let elapsed = tick.as_nanos() * timestamp_raw.saturating_sub(first_raw) as u128 / 100;
+        reference + elapsed.min(u64::MAX as u128) as u64
+    }
+
+    fn header(&self) -> Vec<u8> {
+        let layout = self.layout.unwrap_or(SerLayout {
+            width: 0,
+            height: 0,
+            format: XI_IMG_FORMAT::XI_MONO8,
+            color_id: SER_MONO,
+            bits: 8,
+        });
+        let text = |value: &str| {
+            let mut field = [0u8; 40];
+            let length = value.len().min(field.len());
+            field[..length].copy_from_slice(&value.as_bytes()[..length]);
+            field
+        };
+        let start_time = self.timestamps.first().copied().unwrap_or(0);
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+        header.extend_from_slice(b"LUCAM-RECORDER");
+        header.extend_from_slice(&0i32.to_le_bytes());
+        header.extend_from_slice(&layout.color_id.to_le_bytes());
+        // Most readers interpret 0 as little endian, contrary to the original specification
+        header.extend_from_slice(&0i32.to_le_bytes());
+        header.extend_from_slice(&(layout.width as i32).to_le_bytes());
+        header.extend_from_slice(&(layout.height as i32).to_le_bytes());
+        header.extend_from_slice(&(layout.bits as i32).to_le_bytes());
+        header.extend_from_slice(&(self.timestamps.len() as i32).to_le_bytes());
+        header.extend_from_slice(&text(&self.info.observer));
+        header.extend_from_slice(&text(&self.info.instrument));
+        header.extend_from_slice(&text(&self.info.telescope));
+        // The local time zone is not known, so UTC is used for the local time as well
+        header.extend_from_slice(&start_time.to_le_bytes());
+        header.extend_from_slice(&start_time.to_le_bytes());
+        debug_assert_eq!(header.len(), HEADER_SIZE);
+        header
+    }
+}
+
+impl SerLayout {
+    fn of<T>(image: &Image<'_, T>, bit_depth: Option<u16>) -> io::Result<Self> {
+        use XI_COLOR_FILTER_ARRAY::*;
+        use XI_IMG_FORMAT::*;
+        let format = image.format();
+        let (sample_size, color_id) = match format {
+            XI_MONO8 => (1, SER_MONO),
+            XI_MONO16 => (2, SER_MONO),
+            XI_RAW8 | XI_RAW16 => {
+                let color_id = match image.color_filter_array() {
+                    XI_CFA_BAYER_RGGB => SER_BAYER_RGGB,
+                    XI_CFA_BAYER_GRBG => SER_BAYER_GRBG,
+                    XI_CFA_BAYER_GBRG => SER_BAYER_GBRG,
+                    XI_CFA_BAYER_BGGR => SER_BAYER_BGGR,
+                    _ => SER_MONO,
+                };
+                (if format == XI_RAW8 { 1 } else { 2 }, color_id)
+            }
+            XI_RGB24 | XI_RGB32 => (1, SER_BGR),
+            XI_RGB48 | XI_RGB64 => (2, SER_BGR),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "unsupported image format")),
+        };
+        if sample_size != size_of::<T>() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "pixel type does not match the image format"));
+        }
+        let bits = (sample_size * 8) as u16;
+        Ok(SerLayout {
+            width: image.width(),
+            height: image.height(),
+            format,
+            color_id,
+            bits: bit_depth.filter(|&b| b > 0 && b <= bits).unwrap_or(bits),
+        })
+    }
+}
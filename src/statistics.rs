@@ -0,0 +1,268 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use xiapi_sys::*;
+
+use crate::tiff::cfa_pattern;
+use crate::Image;
+use crate::PixelValue;
+use crate::Roi;
+
+/// Largest number of histogram bins used by default
+const MAX_DEFAULT_BINS: usize = 65536;
+
+/// Number of histogram bins used by default for float images
+const FLOAT_BINS: usize = 1024;
+
+/// Options for [Image::statistics()].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatisticsConfig {
+    /// Region of the image to analyze. The whole image is used if this is `None`.
+    pub roi: Option<Roi>,
+
+    /// Number of significant bits of the pixel values, e.g. 12 for RAW16 images of a 12 bit
+    /// sensor. Defaults to the saturation level reported by the camera or the range of the
+    /// format.
+    pub bit_depth: Option<u32>,
+
+    /// Number of histogram bins. Defaults to one bin per possible value (at most 65536) for
+    /// integer formats and 1024 bins for float images.
+    pub bins: Option<usize>,
+
+    /// Compute the statistics of RAW images with a Bayer color filter separately for the four
+    /// positions of the 2x2 pattern.
+    pub bayer: bool,
+}
+
+/// Histogram of the values of one channel.
+///
+/// The bins have equal width and cover the values from 0 up to the saturation level. Larger
+/// values are counted in the last bin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    bins: Vec<u64>,
+    range: f32,
+}
+
+impl Histogram {
+    /// Number of values in each bin
+    pub fn bins(&self) -> &[u64] {
+        &self.bins
+    }
+
+    /// Range of values covered by one bin
+    pub fn bin_width(&self) -> f32 {
+        self.range / self.bins.len() as f32
+    }
+
+    /// Total number of values
+    pub fn count(&self) -> u64 {
+        self.bins.iter().sum()
+    }
+
+    /// Smallest value of the bin in which the given percentile (0 to 100) of the values falls.
+    ///
+    /// For integer images with one bin per value, this is the exact percentile.
+    pub fn percentile(&self, percent: f32) -> f32 {
+        let count = self.count();
+        let rank = ((percent.clamp(0.0, 100.0) / 100.0 * count as f32).ceil() as u64).max(1);
+        let mut cumulative = 0;
+        for (index, &bin) in self.bins.iter().enumerate() {
+            cumulative += bin;
+            if cumulative >= rank {
+                return index as f32 * self.bin_width();
+            }
+        }
+        0.0
+    }
+}
+
+/// Statistics of the values of one channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelStatistics {
+    /// Number of values
+    pub count: u64,
+
+    /// Smallest value
+    pub min: f32,
+
+    /// Largest value
+    pub max: f32,
+
+    /// Mean value
+    pub mean: f32,
+
+    /// Standard deviation of the values
+    pub std_dev: f32,
+
+    /// Histogram of the values
+    pub histogram: Histogram,
+}
+
+impl ChannelStatistics {
+    /// See [Histogram::percentile()].
+    pub fn percentile(&self, percent: f32) -> f32 {
+        self.histogram.percentile(percent)
+    }
+}
+
+/// Running sums of a channel
+struct Accumulator {
+    bins: Vec<u64>,
+    scale: f32,
+    sum: f64,
+    sum_squares: f64,
+    min: f32,
+    max: f32,
+}
+
+impl Accumulator {
+    fn new(bins: usize, range: f32) -> Self {
+        Accumulator {
+            bins: vec![0; bins],
+            scale: bins as f32 / range,
+            sum: 0.0,
+            sum_squares: 0.0,
+            min: f32::INFINITY,
+            max: f32::NEG_INFINITY,
+        }
+    }
+
+    /// Add values to the histogram and the sums.
+    fn add<T: PixelValue>(&mut self, values: impl Iterator<Item = T>) {
+        let last = self.bins.len() - 1;
+        let (mut sum, mut sum_squares) = (0.0f64, 0.0f64);
+        for value in values {
+            let value = value.to_f32();
+            self.bins[((value * self.scale) as usize).min(last)] += 1;
+            sum += value as f64;
+            sum_squares += (value * value) as f64;
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.sum += sum;
+        self.sum_squares += sum_squares;
+    }
+
+    fn finish(self, range: f32) -> ChannelStatistics {
+        let count: u64 = self.bins.iter().sum();
+        let (mean, variance) = match count {
+            0 => (0.0, 0.0),
+            n => {
+                let mean = self.sum / n as f64;
+                (mean, (self.sum_squares / n as f64 - mean * mean).max(0.0))
+            }
+        };
+        ChannelStatistics {
+            count,
+            min: if count > 0 { self.min } else { 0.0 },
+            max: if count > 0 { self.max } else { 0.0 },
+            mean: mean as f32,
+            std_dev: variance.sqrt() as f32,
+            histogram: Histogram {
+                bins: self.bins,
+                range,
+            },
+        }
+    }
+}
+
+impl<T: PixelValue> Image<'_, T> {
+    /// Compute minimum, maximum, mean, standard deviation and a histogram of each channel.
+    ///
+    /// Channels are returned in the order of the image format, e.g. B, G, R for RGB24 and R, G, B
+    /// for planar formats. With [StatisticsConfig::bayer] set, RAW images with a Bayer filter
+    /// return four channels for the pixels at even/even, odd/even, even/odd and odd/odd (x/y)
+    /// image coordinates, independent of the ROI offset. Use [Image::color_filter_array()] to
+    /// find their colors.
+    ///
+    /// Returns `XI_OUT_OF_RANGE` if the ROI is not inside the image and
+    /// `XI_NOT_SUPPORTED_DATA_FORMAT` for packed transport data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), xiapi_sys::XI_RETURN> {
+    /// use xiapi::XI_IMG_FORMAT::XI_RAW16;
+    /// let mut cam = xiapi::open_device(None)?;
+    /// cam.set_image_data_format(XI_RAW16)?;
+    /// let acq = cam.start_acquisition()?;
+    /// let image = acq.next_image::<u16>(None)?;
+    /// let config = xiapi::StatisticsConfig {
+    ///     bit_depth: Some(acq.image_data_bit_depth()?),
+    ///     bayer: true,
+    ///     ..Default::default()
+    /// };
+    /// for channel in image.statistics(&config)? {
+    ///     println!("mean {} median {}", channel.mean, channel.percentile(50.0));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn statistics(&self, config: &StatisticsConfig) -> Result<Vec<ChannelStatistics>, XI_RETURN> {
+        let nb_channels = self.nb_channels();
+        if nb_channels == 0 || self.format() == XI_IMG_FORMAT::XI_FRM_TRANSPORT_DATA {
+            return Err(XI_RET::XI_NOT_SUPPORTED_DATA_FORMAT as XI_RETURN);
+        }
+        let roi = config.roi.unwrap_or(Roi {
+            offset_x: 0,
+            offset_y: 0,
+            width: self.width(),
+            height: self.height(),
+        });
+        if roi.offset_x as u64 + roi.width as u64 > self.width() as u64
+            || roi.offset_y as u64 + roi.height as u64 > self.height() as u64
+        {
+            return Err(XI_RET::XI_OUT_OF_RANGE as XI_RETURN);
+        }
+        let float = self.format() == XI_IMG_FORMAT::XI_RAW32FLOAT;
+        let range = match (config.bit_depth, float) {
+            (_, true) => 1.0,
+            (Some(bits), false) => 2f32.powi(bits as i32),
+            (None, false) => self.saturation_level().floor() + 1.0,
+        };
+        let bins = match (config.bins, float) {
+            (Some(bins), _) => bins.max(1),
+            (None, true) => FLOAT_BINS,
+            (None, false) => (range as usize).clamp(1, MAX_DEFAULT_BINS),
+        };
+        let bayer = config.bayer
+            && nb_channels == 1
+            && cfa_pattern(self.color_filter_array()).is_some()
+            && matches!(self.format(), XI_IMG_FORMAT::XI_RAW8 | XI_IMG_FORMAT::XI_RAW16);
+        let mut accumulators: Vec<Accumulator> = (0..if bayer { 4 } else { nb_channels })
+            .map(|_| Accumulator::new(bins, range))
+            .collect();
+
+        let (x0, x1) = (roi.offset_x as usize, (roi.offset_x + roi.width) as usize);
+        for y in roi.offset_y as usize..(roi.offset_y + roi.height) as usize {
+            if self.is_planar() {
+                for (channel, accumulator) in accumulators.iter_mut().enumerate() {
+                    let plane = self.plane(channel).unwrap_or(&[]);
+                    let start = self.stride() / std::mem::size_of::<T>() * y;
+                    accumulator.add(plane[start + x0..start + x1].iter().copied());
+                }
+                continue;
+            }
+            let row = match self.row(y) {
+                Some(row) => &row[x0 * nb_channels..x1 * nb_channels],
+                None => continue,
+            };
+            if bayer {
+                let first = (y % 2) * 2 + x0 % 2;
+                let second = (y % 2) * 2 + (x0 + 1) % 2;
+                accumulators[first].add(row.iter().step_by(2).copied());
+                accumulators[second].add(row.iter().skip(1).step_by(2).copied());
+            } else if nb_channels == 1 {
+                accumulators[0].add(row.iter().copied());
+            } else {
+                for (channel, accumulator) in accumulators.iter_mut().enumerate() {
+                    accumulator.add(row.iter().skip(channel).step_by(nb_channels).copied());
+                }
+            }
+        }
+        Ok(accumulators.into_iter().map(|accumulator| accumulator.finish(range)).collect())
+    }
+}
@@ -0,0 +1,608 @@
+/*
+ * Copyright (c) 2024. XIMEA GmbH - All Rights Reserved
+ */
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::mem::size_of;
+use std::path::Path;
+
+use xiapi_sys::*;
+
+use crate::image::format_sample_size;
+use crate::Camera;
+use crate::Image;
+use crate::OwnedImage;
+use crate::PixelValue;
+
+/// Offset of the image data in the files written by [write_tiff_file]
+const DATA_OFFSET: u32 = 8;
+
+// Field types
+const BYTE: u16 = 1;
+const ASCII: u16 = 2;
+const SHORT: u16 = 3;
+const LONG: u16 = 4;
+const RATIONAL: u16 = 5;
+const SRATIONAL: u16 = 10;
+
+// Tags
+pub(crate) const IMAGE_WIDTH: u16 = 256;
+pub(crate) const IMAGE_LENGTH: u16 = 257;
+pub(crate) const BITS_PER_SAMPLE: u16 = 258;
+pub(crate) const COMPRESSION: u16 = 259;
+pub(crate) const PHOTOMETRIC_INTERPRETATION: u16 = 262;
+pub(crate) const IMAGE_DESCRIPTION: u16 = 270;
+pub(crate) const MAKE: u16 = 271;
+pub(crate) const MODEL: u16 = 272;
+pub(crate) const STRIP_OFFSETS: u16 = 273;
+pub(crate) const SAMPLES_PER_PIXEL: u16 = 277;
+pub(crate) const ROWS_PER_STRIP: u16 = 278;
+pub(crate) const STRIP_BYTE_COUNTS: u16 = 279;
+pub(crate) const MAX_SAMPLE_VALUE: u16 = 281;
+pub(crate) const PLANAR_CONFIGURATION: u16 = 284;
+pub(crate) const SOFTWARE: u16 = 305;
+pub(crate) const EXTRA_SAMPLES: u16 = 338;
+pub(crate) const CFA_REPEAT_PATTERN_DIM: u16 = 33421;
+pub(crate) const CFA_PATTERN: u16 = 33422;
+pub(crate) const EXPOSURE_TIME: u16 = 33434;
+pub(crate) const IMAGE_NUMBER: u16 = 37393;
+pub(crate) const BODY_SERIAL_NUMBER: u16 = 42033;
+
+// Photometric interpretations
+pub(crate) const BLACK_IS_ZERO: u16 = 1;
+pub(crate) const RGB: u16 = 2;
+pub(crate) const CFA: u16 = 32803;
+
+/// Metadata of a TIFF file that is not part of the image.
+///
+/// Used when writing with [Image::write_tiff()] and returned by [OwnedImage::read_tiff()].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TiffInfo {
+    /// Number of bits per sample that contain data, e.g. 12 for 12-bit data in 16-bit samples
+    pub significant_bits: Option<u16>,
+
+    /// Serial number of the camera
+    pub serial_number: Option<String>,
+
+    /// Model name of the camera
+    pub model: Option<String>,
+}
+
+impl TiffInfo {
+    /// Read the bit depth, serial number and model of a camera.
+    pub fn for_camera(camera: &Camera) -> Result<Self, XI_RETURN> {
+        Ok(TiffInfo {
+            significant_bits: Some(camera.image_data_bit_depth()? as u16),
+            serial_number: Some(camera.serial_number()?),
+            model: Some(unsafe { camera.param_string(XI_PRM_DEVICE_NAME) }?),
+        })
+    }
+}
+
+/// Entry of a TIFF image file directory
+pub(crate) struct IfdEntry {
+    tag: u16,
+    field_type: u16,
+    count: u32,
+    data: Vec<u8>,
+}
+
+impl IfdEntry {
+    pub(crate) fn byte(tag: u16, values: &[u8]) -> Self {
+        Self::new(tag, BYTE, values.len(), values.to_vec())
+    }
+
+    pub(crate) fn ascii(tag: u16, value: &str) -> Self {
+        let mut data = value.as_bytes().to_vec();
+        data.push(0);
+        Self::new(tag, ASCII, data.len(), data)
+    }
+
+    pub(crate) fn short(tag: u16, values: &[u16]) -> Self {
+        let data = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        Self::new(tag, SHORT, values.len(), data)
+    }
+
+    pub(crate) fn long(tag: u16, values: &[u32]) -> Self {
+        let data = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        Self::new(tag, LONG, values.len(), data)
+    }
+
+    pub(crate) fn rational(tag: u16, values: &[(u32, u32)]) -> Self {
+        let data = values
+            .iter()
+            .flat_map(|(n, d)| n.to_le_bytes().into_iter().chain(d.to_le_bytes()))
+            .collect();
+        Self::new(tag, RATIONAL, values.len(), data)
+    }
+
+    pub(crate) fn srational(tag: u16, values: &[(i32, i32)]) -> Self {
+        let data = values
+            .iter()
+            .flat_map(|(n, d)| n.to_le_bytes().into_iter().chain(d.to_le_bytes()))
+            .collect();
+        Self::new(tag, SRATIONAL, values.len(), data)
+    }
+
+    fn new(tag: u16, field_type: u16, count: usize, data: Vec<u8>) -> Self {
+        IfdEntry {
+            tag,
+            field_type,
+            count: count as u32,
+            data,
+        }
+    }
+}
+
+/// Write a little endian TIFF file with a single directory and the image data in one strip.
+///
+/// The strip offset and byte count are added to the given entries.
+pub(crate) fn write_tiff_file<W: Write>(writer: &mut W, data: &[u8], mut entries: Vec<IfdEntry>) -> io::Result<()> {
+    entries.push(IfdEntry::long(STRIP_OFFSETS, &[DATA_OFFSET]));
+    entries.push(IfdEntry::long(STRIP_BYTE_COUNTS, &[data.len() as u32]));
+    entries.sort_by_key(|entry| entry.tag);
+    let even = |offset: u32| offset + (offset & 1);
+    // The image data directly follows the header, the directory follows the data
+    let ifd_offset = even(DATA_OFFSET + data.len() as u32);
+    let mut value_offset = ifd_offset + 2 + 12 * entries.len() as u32 + 4;
+    writer.write_all(b"II")?;
+    writer.write_all(&42u16.to_le_bytes())?;
+    writer.write_all(&ifd_offset.to_le_bytes())?;
+    writer.write_all(data)?;
+    if data.len() & 1 == 1 {
+        // Directories have to start on a word boundary
+        writer.write_all(&[0])?;
+    }
+    writer.write_all(&(entries.len() as u16).to_le_bytes())?;
+    for entry in &entries {
+        writer.write_all(&entry.tag.to_le_bytes())?;
+        writer.write_all(&entry.field_type.to_le_bytes())?;
+        writer.write_all(&entry.count.to_le_bytes())?;
+        if entry.data.len() <= 4 {
+            let mut value = [0u8; 4];
+            value[..entry.data.len()].copy_from_slice(&entry.data);
+            writer.write_all(&value)?;
+        } else {
+            writer.write_all(&value_offset.to_le_bytes())?;
+            value_offset = even(value_offset + entry.data.len() as u32);
+        }
+    }
+    // No further directories
+    writer.write_all(&0u32.to_le_bytes())?;
+    for entry in entries.iter().filter(|entry| entry.data.len() > 4) {
+        writer.write_all(&entry.data)?;
+        if entry.data.len() & 1 == 1 {
+            writer.write_all(&[0])?;
+        }
+    }
+    Ok(())
+}
+
+/// Write a single strip, uncompressed grayscale TIFF file.
+pub(crate) fn write_gray_tiff<W: Write>(
+    writer: &mut W,
+    width: u32,
+    height: u32,
+    bits_per_sample: u16,
+    data: &[u8],
+) -> io::Result<()> {
+    let entries = vec![
+        IfdEntry::long(IMAGE_WIDTH, &[width]),
+        IfdEntry::long(IMAGE_LENGTH, &[height]),
+        IfdEntry::short(BITS_PER_SAMPLE, &[bits_per_sample]),
+        IfdEntry::short(COMPRESSION, &[1]),
+        IfdEntry::short(PHOTOMETRIC_INTERPRETATION, &[BLACK_IS_ZERO]),
+        IfdEntry::short(SAMPLES_PER_PIXEL, &[1]),
+        IfdEntry::long(ROWS_PER_STRIP, &[height]),
+    ];
+    write_tiff_file(writer, data, entries)
+}
+
+/// TIFF CFA pattern (0 = red, 1 = green, 2 = blue) of a Bayer color filter array
+pub(crate) fn cfa_pattern(cfa: XI_COLOR_FILTER_ARRAY::Type) -> Option<[u8; 4]> {
+    use XI_COLOR_FILTER_ARRAY::*;
+    match cfa {
+        XI_CFA_BAYER_RGGB => Some([0, 1, 1, 2]),
+        XI_CFA_BAYER_BGGR => Some([2, 1, 1, 0]),
+        XI_CFA_BAYER_GRBG => Some([1, 0, 2, 1]),
+        XI_CFA_BAYER_GBRG => Some([1, 2, 0, 1]),
+        _ => None,
+    }
+}
+
+/// Number of samples per pixel and the order in which the samples are stored in TIFF files
+fn sample_layout(format: XI_IMG_FORMAT::Type) -> Option<&'static [usize]> {
+    use XI_IMG_FORMAT::*;
+    match format {
+        XI_MONO8 | XI_MONO16 | XI_RAW8 | XI_RAW16 => Some(&[0]),
+        // The camera delivers BGR(A), TIFF uses RGB(A)
+        XI_RGB24 | XI_RGB48 => Some(&[2, 1, 0]),
+        XI_RGB32 | XI_RGB64 => Some(&[2, 1, 0, 3]),
+        _ => None,
+    }
+}
+
+fn invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl<T: PixelValue> Image<'_, T> {
+    /// Pixel data without padding with the samples of every pixel in the given order, as little
+    /// endian bytes. `channels` is the number of samples per pixel in the image, so channels
+    /// that are not in `order` are dropped.
+    pub(crate) fn packed_samples(&self, channels: usize, order: &[usize]) -> Vec<u8> {
+        let sample_size = size_of::<T>();
+        let pixel_size = channels * sample_size;
+        let row_length = self.width() as usize * pixel_size;
+        let data = self.data_bytes();
+        let output_length = self.width() as usize * order.len() * sample_size * self.height() as usize;
+        let mut samples = Vec::with_capacity(output_length);
+        for y in 0..self.height() as usize {
+            let row = &data[y * self.stride()..][..row_length];
+            for pixel in row.chunks_exact(pixel_size) {
+                for &channel in order {
+                    samples.extend_from_slice(&pixel[channel * sample_size..][..sample_size]);
+                }
+            }
+        }
+        if cfg!(target_endian = "big") {
+            for sample in samples.chunks_exact_mut(sample_size) {
+                sample.reverse();
+            }
+        }
+        samples
+    }
+
+    /// Write the image as uncompressed TIFF file.
+    ///
+    /// MONO, RAW and RGB images with 8 or 16 bits per sample are supported. The CFA
+    /// layout of RAW images, the exposure time and the frame number are stored in the standard
+    /// TIFF/EP tags. All metadata of the image, including gain and timestamp, is also stored in the
+    /// image description, so it can be restored by [OwnedImage::read_tiff()].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[serial_test::file_serial]
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use xiapi::XI_IMG_FORMAT::XI_RAW16;
+    /// let mut cam = xiapi::open_device(None).map_err(|err| format!("xiAPI error {err}"))?;
+    /// cam.set_image_data_format(XI_RAW16).map_err(|err| format!("xiAPI error {err}"))?;
+    /// let info = xiapi::TiffInfo::for_camera(&cam).map_err(|err| format!("xiAPI error {err}"))?;
+    /// let acq = cam.start_acquisition().map_err(|err| format!("xiAPI error {err}"))?;
+    /// let image = acq.next_image::<u16>(None).map_err(|err| format!("xiAPI error {err}"))?;
+    /// image.save_tiff("raw.tif", &info)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_tiff<W: Write>(&self, writer: &mut W, info: &TiffInfo) -> io::Result<()> {
+        let order = sample_layout(self.format()).ok_or_else(|| invalid_input("unsupported image format"))?;
+        let bits = (size_of::<T>() * 8) as u16;
+        if size_of::<T>() != format_sample_size(self.format()) {
+            return Err(invalid_input("pixel type does not match the image format"));
+        }
+        let samples_per_pixel = order.len() as u16;
+        let mut entries = vec![
+            IfdEntry::long(IMAGE_WIDTH, &[self.width()]),
+            IfdEntry::long(IMAGE_LENGTH, &[self.height()]),
+            IfdEntry::short(BITS_PER_SAMPLE, &vec![bits; order.len()]),
+            IfdEntry::short(COMPRESSION, &[1]),
+            IfdEntry::short(SAMPLES_PER_PIXEL, &[samples_per_pixel]),
+            IfdEntry::long(ROWS_PER_STRIP, &[self.height()]),
+            IfdEntry::short(PLANAR_CONFIGURATION, &[1]),
+            IfdEntry::ascii(MAKE, "XIMEA"),
+            IfdEntry::ascii(SOFTWARE, concat!("xiapi ", env!("CARGO_PKG_VERSION"))),
+            IfdEntry::ascii(IMAGE_DESCRIPTION, &self.description()),
+            IfdEntry::rational(EXPOSURE_TIME, &[(self.exposure_time_us(), 1_000_000)]),
+            IfdEntry::long(IMAGE_NUMBER, &[self.nframe()]),
+        ];
+        let cfa = match self.format() {
+            XI_IMG_FORMAT::XI_RAW8 | XI_IMG_FORMAT::XI_RAW16 => cfa_pattern(self.color_filter_array()),
+            _ => None,
+        };
+        let photometric = match (samples_per_pixel, cfa) {
+            (1, Some(pattern)) => {
+                entries.push(IfdEntry::short(CFA_REPEAT_PATTERN_DIM, &[2, 2]));
+                entries.push(IfdEntry::byte(CFA_PATTERN, &pattern));
+                CFA
+            }
+            (1, None) => BLACK_IS_ZERO,
+            _ => RGB,
+        };
+        entries.push(IfdEntry::short(PHOTOMETRIC_INTERPRETATION, &[photometric]));
+        if samples_per_pixel == 4 {
+            // Unspecified data in the fourth sample
+            entries.push(IfdEntry::short(EXTRA_SAMPLES, &[0]));
+        }
+        if let Some(significant_bits) = info.significant_bits.filter(|&b| b > 0 && b < bits) {
+            let max = (1u32 << significant_bits) - 1;
+            entries.push(IfdEntry::short(MAX_SAMPLE_VALUE, &vec![max as u16; order.len()]));
+        }
+        if let Some(serial_number) = &info.serial_number {
+            entries.push(IfdEntry::ascii(BODY_SERIAL_NUMBER, serial_number));
+        }
+        if let Some(model) = &info.model {
+            entries.push(IfdEntry::ascii(MODEL, model));
+        }
+        write_tiff_file(writer, &self.packed_samples(self.nb_channels(), order), entries)
+    }
+
+    /// Write the image to a TIFF file. See [Self::write_tiff()].
+    pub fn save_tiff<P: AsRef<Path>>(&self, path: P, info: &TiffInfo) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_tiff(&mut writer, info)?;
+        writer.flush()
+    }
+
+    /// Metadata of the image as text
+    fn description(&self) -> String {
+        format!(
+            "xiapi format={} cfa={} nframe={} acq_nframe={} timestamp_raw={} exposure_time_us={} \
+             gain_db={} black_level={} image_user_data={} data_saturation={}",
+            self.format(),
+            self.color_filter_array(),
+            self.nframe(),
+            self.acq_nframe(),
+            self.timestamp_raw(),
+            self.exposure_time_us(),
+            self.gain_db(),
+            self.black_level(),
+            self.image_user_data(),
+            self.data_saturation()
+        )
+    }
+}
+
+/// Image file directory read from a TIFF file
+struct TiffDirectory {
+    little_endian: bool,
+    entries: BTreeMap<u16, (u16, u32, Vec<u8>)>,
+}
+
+impl TiffDirectory {
+    /// Read the first directory of a TIFF file.
+    fn read<R: Read + Seek>(reader: &mut R) -> io::Result<Self> {
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header)?;
+        let little_endian = match &header[..4] {
+            b"II\x2a\x00" => true,
+            b"MM\x00\x2a" => false,
+            _ => return Err(invalid_data("not a TIFF file")),
+        };
+        let mut directory = TiffDirectory {
+            little_endian,
+            entries: BTreeMap::new(),
+        };
+        let ifd_offset = directory.u32_at(&header[4..]);
+        reader.seek(SeekFrom::Start(ifd_offset as u64))?;
+        let mut count = [0u8; 2];
+        reader.read_exact(&mut count)?;
+        let mut raw_entries = vec![0u8; 12 * directory.u16_at(&count) as usize];
+        reader.read_exact(&mut raw_entries)?;
+        for raw in raw_entries.chunks_exact(12) {
+            let tag = directory.u16_at(&raw[0..]);
+            let field_type = directory.u16_at(&raw[2..]);
+            let count = directory.u32_at(&raw[4..]);
+            let size = match field_type {
+                BYTE | ASCII => 1,
+                SHORT => 2,
+                LONG => 4,
+                RATIONAL | SRATIONAL => 8,
+                _ => continue,
+            } * count as usize;
+            let data = match size <= 4 {
+                true => raw[8..8 + size].to_vec(),
+                false => {
+                    let mut data = vec![0u8; size];
+                    reader.seek(SeekFrom::Start(directory.u32_at(&raw[8..]) as u64))?;
+                    reader.read_exact(&mut data)?;
+                    data
+                }
+            };
+            directory.entries.insert(tag, (field_type, count, data));
+        }
+        Ok(directory)
+    }
+
+    fn u16_at(&self, bytes: &[u8]) -> u16 {
+        let bytes = [bytes[0], bytes[1]];
+        match self.little_endian {
+            true => u16::from_le_bytes(bytes),
+            false => u16::from_be_bytes(bytes),
+        }
+    }
+
+    fn u32_at(&self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        match self.little_endian {
+            true => u32::from_le_bytes(bytes),
+            false => u32::from_be_bytes(bytes),
+        }
+    }
+
+    /// Integer values of a tag
+    fn values(&self, tag: u16) -> Option<Vec<u32>> {
+        let (field_type, _, data) = self.entries.get(&tag)?;
+        match *field_type {
+            BYTE => Some(data.iter().map(|&v| v as u32).collect()),
+            SHORT => Some(data.chunks_exact(2).map(|v| self.u16_at(v) as u32).collect()),
+            LONG => Some(data.chunks_exact(4).map(|v| self.u32_at(v)).collect()),
+            _ => None,
+        }
+    }
+
+    fn value(&self, tag: u16) -> Option<u32> {
+        self.values(tag)?.first().copied()
+    }
+
+    fn rational(&self, tag: u16) -> Option<f64> {
+        match self.entries.get(&tag)? {
+            (RATIONAL, _, data) if data.len() >= 8 => {
+                let denominator = self.u32_at(&data[4..]);
+                Some(self.u32_at(data) as f64 / denominator.max(1) as f64)
+            }
+            _ => None,
+        }
+    }
+
+    fn text(&self, tag: u16) -> Option<String> {
+        let (_, _, data) = self.entries.get(&tag)?;
+        let length = data.iter().position(|&c| c == 0).unwrap_or(data.len());
+        String::from_utf8(data[..length].to_vec()).ok()
+    }
+}
+
+impl<T: PixelValue> OwnedImage<T> {
+    /// Read an uncompressed TIFF file with 8 or 16 bits per sample.
+    ///
+    /// Metadata written by [Image::write_tiff()] is restored. Other files are read as MONO,
+    /// RAW (for CFA data) or RGB images.
+    pub fn read_tiff<R: Read + Seek>(reader: &mut R) -> io::Result<(Self, TiffInfo)> {
+        let directory = TiffDirectory::read(reader)?;
+        let width = directory.value(IMAGE_WIDTH).ok_or_else(|| invalid_data("missing image width"))?;
+        let height = directory.value(IMAGE_LENGTH).ok_or_else(|| invalid_data("missing image length"))?;
+        let samples_per_pixel = directory.value(SAMPLES_PER_PIXEL).unwrap_or(1);
+        let bits = directory.value(BITS_PER_SAMPLE).unwrap_or(1);
+        if directory.value(COMPRESSION).unwrap_or(1) != 1 {
+            return Err(invalid_data("compressed TIFF files are not supported"));
+        }
+        if bits as usize != size_of::<T>() * 8 {
+            return Err(invalid_input("pixel type does not match the bits per sample"));
+        }
+        let photometric = directory.value(PHOTOMETRIC_INTERPRETATION).unwrap_or(BLACK_IS_ZERO as u32);
+        let mut xi_img = XI_IMG {
+            size: size_of::<XI_IMG>() as u32,
+            width,
+            height,
+            ..Default::default()
+        };
+        xi_img.frm = match (samples_per_pixel, bits, photometric as u16) {
+            (1, 8, CFA) => XI_IMG_FORMAT::XI_RAW8,
+            (1, 16, CFA) => XI_IMG_FORMAT::XI_RAW16,
+            (1, 8, _) => XI_IMG_FORMAT::XI_MONO8,
+            (1, 16, _) => XI_IMG_FORMAT::XI_MONO16,
+            (3, 8, _) => XI_IMG_FORMAT::XI_RGB24,
+            (4, 8, _) => XI_IMG_FORMAT::XI_RGB32,
+            (3, 16, _) => XI_IMG_FORMAT::XI_RGB48,
+            (4, 16, _) => XI_IMG_FORMAT::XI_RGB64,
+            _ => return Err(invalid_data("unsupported TIFF layout")),
+        };
+        if let Some(pattern) = directory.values(CFA_PATTERN) {
+            use XI_COLOR_FILTER_ARRAY::*;
+            xi_img.color_filter_array = [XI_CFA_BAYER_RGGB, XI_CFA_BAYER_BGGR, XI_CFA_BAYER_GRBG, XI_CFA_BAYER_GBRG]
+                .into_iter()
+                .find(|&cfa| {
+                    cfa_pattern(cfa).map(|p| p.map(|v| v as u32).to_vec()) == Some(pattern.clone())
+                })
+                .unwrap_or(XI_CFA_NONE);
+        }
+        if let Some(exposure_time) = directory.rational(EXPOSURE_TIME) {
+            xi_img.exposure_time_us = (exposure_time * 1e6).round() as u32;
+        }
+        if let Some(nframe) = directory.value(IMAGE_NUMBER) {
+            xi_img.nframe = nframe;
+        }
+        if let Some(description) = directory.text(IMAGE_DESCRIPTION) {
+            let format = xi_img.frm;
+            apply_description(&mut xi_img, &description);
+            // Only accept formats with the same layout, e.g. MONO8 instead of RAW8
+            if sample_layout(xi_img.frm).map(<[usize]>::len) != Some(samples_per_pixel as usize)
+                || format_sample_size(xi_img.frm) != size_of::<T>()
+            {
+                xi_img.frm = format;
+            }
+        }
+
+        // Read the strips
+        let offsets = directory.values(STRIP_OFFSETS).ok_or_else(|| invalid_data("missing strip offsets"))?;
+        let counts = directory.values(STRIP_BYTE_COUNTS).ok_or_else(|| invalid_data("missing strip byte counts"))?;
+        let length = width as usize * height as usize * samples_per_pixel as usize * size_of::<T>();
+        let mut bytes = Vec::with_capacity(length);
+        for (&offset, &count) in offsets.iter().zip(&counts) {
+            let start = bytes.len();
+            bytes.resize(start + count as usize, 0);
+            reader.seek(SeekFrom::Start(offset as u64))?;
+            reader.read_exact(&mut bytes[start..])?;
+        }
+        if bytes.len() < length {
+            return Err(invalid_data("image data is incomplete"));
+        }
+        bytes.truncate(length);
+        if size_of::<T>() > 1 && directory.little_endian != cfg!(target_endian = "little") {
+            for sample in bytes.chunks_exact_mut(size_of::<T>()) {
+                sample.reverse();
+            }
+        }
+        if samples_per_pixel >= 3 {
+            // Back to the BGR(A) order of the camera
+            let sample_size = size_of::<T>();
+            for pixel in bytes.chunks_exact_mut(samples_per_pixel as usize * sample_size) {
+                for i in 0..sample_size {
+                    pixel.swap(i, 2 * sample_size + i);
+                }
+            }
+        }
+        let mut data = vec![T::default(); length / size_of::<T>()];
+        // Pixel values are plain numbers, so any byte pattern is a valid value
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), data.as_mut_ptr() as *mut u8, length);
+        }
+        xi_img.bp_size = length as u32;
+
+        let significant_bits = directory
+            .value(MAX_SAMPLE_VALUE)
+            .map(|max| 32 - max.leading_zeros() as u16)
+            .filter(|&significant| significant < bits as u16);
+        let info = TiffInfo {
+            significant_bits,
+            serial_number: directory.text(BODY_SERIAL_NUMBER),
+            model: directory.text(MODEL),
+        };
+        Ok((OwnedImage { xi_img, data }, info))
+    }
+
+    /// Read a TIFF file. See [Self::read_tiff()].
+    pub fn open_tiff<P: AsRef<Path>>(path: P) -> io::Result<(Self, TiffInfo)> {
+        Self::read_tiff(&mut BufReader::new(File::open(path)?))
+    }
+}
+
+/// Restore the metadata that was written by [Image::description]
+fn apply_description(xi_img: &mut XI_IMG, description: &str) {
+    let mut words = description.split_whitespace();
+    if words.next() != Some("xiapi") {
+        return;
+    }
+    for (key, value) in words.filter_map(|word| word.split_once('=')) {
+        let int = || value.parse::<u32>().ok();
+        match key {
+            "format" => xi_img.frm = int().unwrap_or(xi_img.frm),
+            "cfa" => xi_img.color_filter_array = int().unwrap_or(xi_img.color_filter_array),
+            "nframe" => xi_img.nframe = int().unwrap_or(xi_img.nframe),
+            "acq_nframe" => xi_img.acq_nframe = int().unwrap_or(0),
+            "timestamp_raw" => {
+                let timestamp = value.parse::<u64>().unwrap_or(0);
+                xi_img.tsSec = (timestamp >> 32) as u32;
+                xi_img.tsUSec = timestamp as u32;
+            }
+            "exposure_time_us" => xi_img.exposure_time_us = int().unwrap_or(0),
+            "gain_db" => xi_img.gain_db = value.parse().unwrap_or(0.0),
+            "black_level" => xi_img.black_level = int().unwrap_or(0),
+            "image_user_data" => xi_img.image_user_data = int().unwrap_or(0),
+            "data_saturation" => xi_img.data_saturation = value.parse().unwrap_or(0.0),
+            _ => {}
+        }
+    }
+}